@@ -1,11 +1,20 @@
 // PDA Seeds
+pub const PROGRAM_CONFIG_SEED: &[u8] = b"program_config";
 pub const POOL_SEED: &[u8] = b"pool";
+pub const VAULT_SEED: &[u8] = b"vault";
+pub const SHARE_MINT_SEED: &[u8] = b"share_mint";
+pub const PRICE_FEED_SEED: &[u8] = b"price_feed";
 pub const LOAN_SEED: &[u8] = b"loan";
+pub const ALLOWANCE_SEED: &[u8] = b"allowance";
 pub const DARK_POOL_SEED: &[u8] = b"dark_pool";
 pub const PROOF_SEED: &[u8] = b"proof";
 pub const ORDER_SEED: &[u8] = b"order";
 pub const AUCTION_SEED: &[u8] = b"auction";
 pub const BID_SEED: &[u8] = b"bid";
+pub const VERIFIER_SEED: &[u8] = b"verifier";
+pub const MARKET_SEED: &[u8] = b"market";
+pub const ESCROW_SEED: &[u8] = b"escrow";
+pub const BATCH_SEED: &[u8] = b"batch";
 
 // Account Space Components
 pub const DISCRIMINATOR: usize = 8;
@@ -13,23 +22,99 @@ pub const PUBKEY_SIZE: usize = 32;
 pub const U64_SIZE: usize = 8;
 pub const U8_SIZE: usize = 1;
 pub const I64_SIZE: usize = 8;
+pub const I32_SIZE: usize = 4;
 pub const BOOL_SIZE: usize = 1;
 pub const VEC_PREFIX: usize = 4;
+pub const U128_SIZE: usize = 16;
+pub const HASH_SIZE: usize = 32;
 
 // Account Sizes
-pub const POOL_SIZE: usize = DISCRIMINATOR + PUBKEY_SIZE + U8_SIZE + U64_SIZE + U64_SIZE;
+pub const PROGRAM_CONFIG_SIZE: usize = DISCRIMINATOR + PUBKEY_SIZE + U8_SIZE;
+pub const POOL_SIZE: usize = DISCRIMINATOR + PUBKEY_SIZE + U8_SIZE + PUBKEY_SIZE + PUBKEY_SIZE + U64_SIZE + U64_SIZE + HASH_SIZE
+    + U64_SIZE + U64_SIZE + U64_SIZE + U64_SIZE + U128_SIZE + I64_SIZE + PUBKEY_SIZE
+    + U64_SIZE + U64_SIZE + U64_SIZE + U64_SIZE + PUBKEY_SIZE + U64_SIZE + U64_SIZE
+    + U64_SIZE + U64_SIZE + U64_SIZE + PUBKEY_SIZE + U64_SIZE + U64_SIZE + U64_SIZE + U64_SIZE;
+pub const PRICE_FEED_SIZE: usize = 
+    DISCRIMINATOR + PUBKEY_SIZE + PUBKEY_SIZE + I64_SIZE + I32_SIZE + U64_SIZE + I64_SIZE + U8_SIZE;
 pub const DARK_POOL_ORDER_BOOK_SIZE: usize = 
     DISCRIMINATOR + PUBKEY_SIZE + U64_SIZE + U64_SIZE + U8_SIZE;
+pub const MARKET_CONFIG_SIZE: usize = 
+    DISCRIMINATOR + PUBKEY_SIZE + U64_SIZE + U64_SIZE + U64_SIZE + U64_SIZE + U64_SIZE + HASH_SIZE + U8_SIZE
+    + PUBKEY_SIZE + PUBKEY_SIZE + PUBKEY_SIZE + PUBKEY_SIZE + U64_SIZE + U64_SIZE + U64_SIZE + U64_SIZE;
+pub const BATCH_SIZE: usize = 
+    DISCRIMINATOR + U64_SIZE + PUBKEY_SIZE + U64_SIZE + U64_SIZE + U8_SIZE + U64_SIZE
+    + VEC_PREFIX + MAX_BATCH_ORDERS * PUBKEY_SIZE + VEC_PREFIX + MAX_BATCH_ORDERS * U64_SIZE + U8_SIZE;
 pub const PROOF_ACCOUNT_SIZE: usize = 
     DISCRIMINATOR + PUBKEY_SIZE + U64_SIZE + VEC_PREFIX + 2048 + VEC_PREFIX + 2048;
 pub const ENCRYPTED_ORDER_SIZE: usize = 
-    DISCRIMINATOR + PUBKEY_SIZE + U64_SIZE + VEC_PREFIX + 512 + PUBKEY_SIZE + I64_SIZE + BOOL_SIZE + U64_SIZE;
+    DISCRIMINATOR + PUBKEY_SIZE + PUBKEY_SIZE + U64_SIZE + VEC_PREFIX + 512 + PUBKEY_SIZE + I64_SIZE + BOOL_SIZE + U64_SIZE
+    + BOOL_SIZE + U64_SIZE + I64_SIZE;
 pub const LIQUIDATION_AUCTION_SIZE: usize = 
-    DISCRIMINATOR + PUBKEY_SIZE + U64_SIZE + U64_SIZE + U64_SIZE + I64_SIZE + U64_SIZE + BOOL_SIZE + PUBKEY_SIZE + U64_SIZE + U8_SIZE;
+    DISCRIMINATOR + PUBKEY_SIZE + PUBKEY_SIZE + U64_SIZE + U64_SIZE + U64_SIZE + I64_SIZE + U64_SIZE + BOOL_SIZE + PUBKEY_SIZE + U64_SIZE + U8_SIZE;
 pub const ENCRYPTED_BID_SIZE: usize = 
-    DISCRIMINATOR + PUBKEY_SIZE + U64_SIZE + VEC_PREFIX + 512 + VEC_PREFIX + 512 + I64_SIZE;
+    DISCRIMINATOR + PUBKEY_SIZE + U64_SIZE + VEC_PREFIX + 512 + VEC_PREFIX + PROOF_DATA_SIZE + I64_SIZE;
+pub const VERIFIER_CONFIG_SIZE: usize = 
+    DISCRIMINATOR + PUBKEY_SIZE + U64_SIZE + U64_SIZE + VEC_PREFIX + VERIFYING_KEY_MAX_LEN + VEC_PREFIX + VERIFYING_KEY_MAX_LEN + U8_SIZE;
 pub const USER_LOAN_SIZE: usize = 
-    DISCRIMINATOR + PUBKEY_SIZE + U64_SIZE + VEC_PREFIX + 512 + U64_SIZE + VEC_PREFIX + 512 + VEC_PREFIX + 512 + BOOL_SIZE + I64_SIZE;
+    DISCRIMINATOR + PUBKEY_SIZE + PUBKEY_SIZE + U64_SIZE + VEC_PREFIX + ENCRYPTED_PROOF_SIZE + U64_SIZE + VEC_PREFIX + ENCRYPTED_PROOF_SIZE + VEC_PREFIX + ENCRYPTED_PROOF_SIZE + BOOL_SIZE + I64_SIZE
+    + U128_SIZE + PUBKEY_SIZE;
+pub const BORROW_ALLOWANCE_SIZE: usize = 
+    DISCRIMINATOR + PUBKEY_SIZE + PUBKEY_SIZE + PUBKEY_SIZE + U64_SIZE + U8_SIZE;
 
 // Initial Values
-pub const INITIAL_BATCH_ID: u64 = 1;
\ No newline at end of file
+pub const INITIAL_BATCH_ID: u64 = 1;
+
+// Circuit Types (must match circuits/obsidian_circuits/src/main.nr)
+pub const COMPLIANCE_CIRCUIT: u64 = 1;
+pub const DARK_POOL_CIRCUIT: u64 = 2;
+pub const AUCTION_CIRCUIT: u64 = 3;
+pub const LOAN_HEALTH_CIRCUIT: u64 = 4;
+pub const LIQUIDATION_CIRCUIT: u64 = 5;
+
+// Groth16 Proof Layout
+pub const FIELD_SIZE: usize = 32;
+pub const G1_SIZE: usize = 64;
+pub const G2_SIZE: usize = 128;
+pub const NUM_PUBLIC_INPUTS: usize = 14;
+pub const PUBLIC_INPUTS_SIZE: usize = NUM_PUBLIC_INPUTS * FIELD_SIZE;
+pub const GROTH16_PROOF_SIZE: usize = G1_SIZE + G2_SIZE + G1_SIZE;
+pub const PROOF_DATA_SIZE: usize = PUBLIC_INPUTS_SIZE + GROTH16_PROOF_SIZE;
+pub const ENCRYPTED_PROOF_SIZE: usize = PUBKEY_SIZE + PROOF_DATA_SIZE;
+pub const VERIFYING_KEY_SIZE: usize = 
+    G1_SIZE + G2_SIZE + G2_SIZE + G2_SIZE + (NUM_PUBLIC_INPUTS + 1) * G1_SIZE;
+pub const VERIFYING_KEY_MAX_LEN: usize = 2048;
+
+// Lending Parameters
+pub const BPS_DENOMINATOR: u64 = 10_000;
+pub const DEFAULT_MAX_LTV_BPS: u64 = 7_500;
+pub const DEFAULT_LIQUIDATION_THRESHOLD_BPS: u64 = 8_000;
+pub const DEFAULT_CLOSE_FACTOR_BPS: u64 = 5_000;
+pub const DEFAULT_LIQUIDATION_BONUS_BPS: u64 = 500;
+pub const DEFAULT_RESERVE_FACTOR_BPS: u64 = 1_000;
+pub const DEFAULT_ORIGINATION_FEE_BPS: u64 = 10;
+pub const DEFAULT_FLASH_LOAN_FEE_BPS: u64 = 9;
+
+// Liquidation Auctions
+// Winning bidders have this long after bidding closes to settle before
+// anyone can cancel the auction and return its collateral to the loan
+pub const AUCTION_SETTLE_WINDOW_SECONDS: i64 = 3_600;
+
+// Interest Rate Model
+pub const INDEX_SCALE: u128 = 1_000_000_000_000_000_000;
+pub const SECONDS_PER_YEAR: u64 = 31_536_000;
+pub const DEFAULT_BASE_RATE_BPS: u64 = 200;
+pub const DEFAULT_SLOPE1_BPS: u64 = 400;
+pub const DEFAULT_OPTIMAL_UTILIZATION_BPS: u64 = 8_000;
+pub const DEFAULT_SLOPE2_BPS: u64 = 7_500;
+
+// Dark Pool
+// Clearing prices are quote base units per base unit, scaled by PRICE_SCALE
+pub const PRICE_SCALE: u128 = 1_000_000;
+// Escrow is padded to lot_size * 2^tier so deposits only leak a coarse size
+pub const MAX_ESCROW_TIER: u8 = 32;
+pub const MAX_BATCH_ORDERS: usize = 16;
+
+// Oracle
+pub const MAX_PRICE_AGE_SECONDS: i64 = 60;
+pub const MAX_CONFIDENCE_BPS: u64 = 200;
+pub const VALUE_DECIMALS: i32 = 6;
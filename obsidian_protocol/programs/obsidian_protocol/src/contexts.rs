@@ -1,7 +1,47 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::Token as TokenProgram;
+use anchor_lang::solana_program::sysvar::instructions as sysvar_instructions;
+use anchor_spl::token::{Mint, Token as TokenProgram, TokenAccount};
+use crate::constants::*;
 use crate::state::*;
 use crate::errors::ErrorCode;
+use crate::program::ObsidianProtocol;
+
+// Only the program's upgrade authority can create the admin config
+#[derive(Accounts)]
+pub struct InitializeProgramConfig<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 1,
+        seeds = [b"program_config"],
+        bump
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ ErrorCode::Unauthorized)]
+    pub program: Program<'info, ObsidianProtocol>,
+    
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()) @ ErrorCode::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateProgramConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.admin == admin.key() @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    pub admin: Signer<'info>,
+}
 
 #[derive(Accounts)]
 #[instruction(bump: u8)]
@@ -9,186 +49,1034 @@ pub struct InitializePool<'info> {
     #[account(
         init,
         payer = authority,
-        space = 8 + 32 + 1 + 8 + 8,
-        seeds = [b"pool"],
+        space = 8 + 32 + 1 + 32 + 32 + 8 + 8 + 32 + 8 + 8 + 8 + 8 + 16 + 8 + 32 + 8 + 8 + 8 + 8 + 32 + 8 + 8 + 8 + 8 + 8 + 32 + 8 + 8 + 8 + 8,
+        seeds = [b"pool", mint.key().as_ref()],
+        bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    pub mint: Account<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"vault", pool.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = pool
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"share_mint", pool.key().as_ref()],
+        bump,
+        mint::decimals = mint.decimals,
+        mint::authority = pool
+    )]
+    pub share_mint: Account<'info, Mint>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Program<'info, TokenProgram>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePool<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+        constraint = pool.authority == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CollectReserves<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump,
+        constraint = pool.authority == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        mut,
+        address = pool.vault @ ErrorCode::InvalidVault
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        address = pool.treasury @ ErrorCode::InvalidTreasury,
+        token::mint = pool.mint
+    )]
+    pub treasury: Account<'info, TokenAccount>,
+    
+    pub authority: Signer<'info>,
+    
+    pub token_program: Program<'info, TokenProgram>,
+}
+
+#[derive(Accounts)]
+pub struct InitializePriceFeed<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 32 + 8 + 4 + 8 + 8 + 1,
+        seeds = [b"price_feed", mint.key().as_ref()],
+        bump
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+    
+    pub mint: Account<'info, Mint>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdatePriceFeed<'info> {
+    #[account(
+        mut,
+        seeds = [b"price_feed", price_feed.mint.as_ref()],
+        bump = price_feed.bump,
+        constraint = price_feed.authority == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub price_feed: Account<'info, PriceFeed>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(circuit_type: u64)]
+pub struct InitializeVerifier<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 8 + 8 + 4 + 2048 + 4 + 2048 + 1,
+        seeds = [b"verifier", circuit_type.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub verifier_config: Account<'info, VerifierConfig>,
+    
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateVerifier<'info> {
+    #[account(
+        mut,
+        seeds = [b"verifier", verifier_config.circuit_type.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub verifier_config: Account<'info, VerifierConfig>,
+    
+    // Key rotation follows the current program admin, not whoever created
+    // the verifier
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Deposit<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + 32 + 32 + 8 + 4 + 736 + 8 + 4 + 736 + 4 + 736 + 1 + 8 + 16 + 32,
+        seeds = [b"loan", pool.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub user_loan: Account<'info, UserLoan>,
+    
+    #[account(
+        seeds = [b"verifier", COMPLIANCE_CIRCUIT.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub verifier_config: Account<'info, VerifierConfig>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    #[account(
+        mut,
+        token::mint = pool.mint,
+        token::authority = user
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        address = pool.vault @ ErrorCode::InvalidVault
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, TokenProgram>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Supply<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        mut,
+        address = pool.share_mint @ ErrorCode::InvalidShareMint
+    )]
+    pub share_mint: Account<'info, Mint>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    #[account(
+        mut,
+        token::mint = pool.mint,
+        token::authority = user
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = pool.share_mint
+    )]
+    pub user_share_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        address = pool.vault @ ErrorCode::InvalidVault
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, TokenProgram>,
+}
+
+#[derive(Accounts)]
+pub struct Redeem<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        mut,
+        address = pool.share_mint @ ErrorCode::InvalidShareMint
+    )]
+    pub share_mint: Account<'info, Mint>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    #[account(
+        mut,
+        token::mint = pool.mint,
+        token::authority = user
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        token::mint = pool.share_mint,
+        token::authority = user
+    )]
+    pub user_share_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        address = pool.vault @ ErrorCode::InvalidVault
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, TokenProgram>,
+}
+
+#[derive(Accounts)]
+pub struct FlashBorrow<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    pub user: Signer<'info>,
+    
+    #[account(
+        mut,
+        token::mint = pool.mint
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        address = pool.vault @ ErrorCode::InvalidVault
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Instructions sysvar, checked by address
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: AccountInfo<'info>,
+    
+    pub token_program: Program<'info, TokenProgram>,
+}
+
+#[derive(Accounts)]
+pub struct FlashRepay<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    pub user: Signer<'info>,
+    
+    #[account(
+        mut,
+        token::mint = pool.mint,
+        token::authority = user
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        address = pool.vault @ ErrorCode::InvalidVault
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+    
+    /// CHECK: Instructions sysvar, checked by address
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions: AccountInfo<'info>,
+    
+    pub token_program: Program<'info, TokenProgram>,
+}
+
+#[derive(Accounts)]
+pub struct SetBorrowAllowance<'info> {
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + 32 + 32 + 32 + 8 + 1,
+        seeds = [b"allowance", pool.key().as_ref(), owner.key().as_ref(), delegate.key().as_ref()],
+        bump
+    )]
+    pub borrow_allowance: Account<'info, BorrowAllowance>,
+    
+    /// CHECK: Only used as a seed and recorded as the delegate
+    pub delegate: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct Borrow<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        mut,
+        seeds = [b"loan", pool.key().as_ref(), user_loan.owner.as_ref()],
+        bump
+    )]
+    pub user_loan: Account<'info, UserLoan>,
+    
+    // Required when `user` borrows on behalf of the loan owner
+    #[account(
+        mut,
+        seeds = [b"allowance", pool.key().as_ref(), user_loan.owner.as_ref(), user.key().as_ref()],
+        bump = borrow_allowance.bump
+    )]
+    pub borrow_allowance: Option<Account<'info, BorrowAllowance>>,
+    
+    #[account(
+        seeds = [b"verifier", LOAN_HEALTH_CIRCUIT.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub verifier_config: Account<'info, VerifierConfig>,
+    
+    /// CHECK: Price account validated by the oracle adapter
+    #[account(address = pool.oracle @ ErrorCode::InvalidOracle)]
+    pub oracle: AccountInfo<'info>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    #[account(
+        mut,
+        token::mint = pool.mint,
+        token::authority = user
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        address = pool.vault @ ErrorCode::InvalidVault
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, TokenProgram>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawCollateral<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        mut,
+        seeds = [b"loan", pool.key().as_ref(), user.key().as_ref()],
+        bump
+    )]
+    pub user_loan: Account<'info, UserLoan>,
+    
+    #[account(
+        seeds = [b"verifier", LOAN_HEALTH_CIRCUIT.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub verifier_config: Account<'info, VerifierConfig>,
+    
+    /// CHECK: Price account validated by the oracle adapter
+    #[account(address = pool.oracle @ ErrorCode::InvalidOracle)]
+    pub oracle: AccountInfo<'info>,
+    
+    #[account(mut)]
+    pub user: Signer<'info>,
+    
+    #[account(
+        mut,
+        token::mint = pool.mint,
+        token::authority = user
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        address = pool.vault @ ErrorCode::InvalidVault
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, TokenProgram>,
+}
+
+#[derive(Accounts)]
+pub struct Repay<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        mut,
+        seeds = [b"loan", pool.key().as_ref(), user_loan.owner.as_ref()],
+        bump
+    )]
+    pub user_loan: Account<'info, UserLoan>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    #[account(
+        mut,
+        token::mint = pool.mint,
+        token::authority = payer
+    )]
+    pub payer_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        address = pool.vault @ ErrorCode::InvalidVault
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, TokenProgram>,
+}
+
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct Liquidate<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        mut,
+        seeds = [b"loan", pool.key().as_ref(), user_loan.owner.as_ref()],
+        bump
+    )]
+    pub user_loan: Account<'info, UserLoan>,
+    
+    #[account(
+        seeds = [b"verifier", LIQUIDATION_CIRCUIT.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub verifier_config: Account<'info, VerifierConfig>,
+    
+    /// CHECK: Price account validated by the oracle adapter
+    #[account(address = pool.oracle @ ErrorCode::InvalidOracle)]
+    pub oracle: AccountInfo<'info>,
+    
+    #[account(mut)]
+    pub liquidator: Signer<'info>,
+    
+    #[account(
+        mut,
+        token::mint = pool.mint,
+        token::authority = liquidator
+    )]
+    pub liquidator_token_account: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        address = pool.vault @ ErrorCode::InvalidVault
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
+    
+    pub token_program: Program<'info, TokenProgram>,
+}
+
+#[derive(Accounts)]
+pub struct CloseLoan<'info> {
+    #[account(
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        mut,
+        seeds = [b"loan", pool.key().as_ref(), owner.key().as_ref()],
+        bump,
+        close = owner
+    )]
+    pub user_loan: Account<'info, UserLoan>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WriteOffBadDebt<'info> {
+    #[account(
+        mut,
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        mut,
+        seeds = [b"loan", pool.key().as_ref(), user_loan.owner.as_ref()],
+        bump
+    )]
+    pub user_loan: Account<'info, UserLoan>,
+    
+    pub caller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(bump: u8)]
+pub struct InitializeDarkPool<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 8 + 8 + 1,
+        seeds = [b"dark_pool"],
+        bump
+    )]
+    pub order_book: Account<'info, DarkPoolOrderBook>,
+    
+    // The order book authority picks fills and clearing prices, so only the
+    // program admin may take it
+    #[account(
+        seeds = [b"program_config"],
+        bump = program_config.bump,
+        constraint = program_config.admin == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub program_config: Account<'info, ProgramConfig>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(market_id: u64)]
+pub struct InitializeMarket<'info> {
+    #[account(
+        constraint = order_book.authority == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub order_book: Account<'info, DarkPoolOrderBook>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 32 + 8 + 8 + 8 + 8 + 8 + 32 + 1 + 32 + 32 + 32 + 32 + 8 + 8 + 8 + 8,
+        seeds = [b"market", market_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub market_config: Account<'info, MarketConfig>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMarket<'info> {
+    #[account(
+        mut,
+        seeds = [b"market", market_config.market_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = market_config.authority == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub market_config: Account<'info, MarketConfig>,
+    
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeMarketVaults<'info> {
+    #[account(
+        mut,
+        seeds = [b"market", market_config.market_id.to_le_bytes().as_ref()],
+        bump = market_config.bump,
+        constraint = market_config.authority == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub market_config: Account<'info, MarketConfig>,
+    
+    pub base_mint: Account<'info, Mint>,
+    
+    pub quote_mint: Account<'info, Mint>,
+    
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"escrow", market_config.key().as_ref(), base_mint.key().as_ref()],
+        bump,
+        token::mint = base_mint,
+        token::authority = market_config
+    )]
+    pub base_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        init,
+        payer = authority,
+        seeds = [b"escrow", market_config.key().as_ref(), quote_mint.key().as_ref()],
+        bump,
+        token::mint = quote_mint,
+        token::authority = market_config
+    )]
+    pub quote_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        seeds = [b"dark_pool"],
+        bump = order_book.bump
+    )]
+    pub order_book: Account<'info, DarkPoolOrderBook>,
+    
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + 8 + 32 + 8 + 8 + 1 + 8 + 4 + 512 + 4 + 128 + 1,
+        seeds = [b"batch", order_book.next_batch_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub batch: Account<'info, Batch>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub token_program: Program<'info, TokenProgram>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(order_id: u64)]
+pub struct CreateProofAccount<'info> {
+    #[account(
+        init,
+        payer = user,
+        space = 8 + 32 + 8 + 4 + 2048 + 4 + 2048,
+        seeds = [b"proof", user.key().as_ref(), order_id.to_le_bytes().as_ref()],
         bump
     )]
-    pub pool: Account<'info, Pool>,
+    pub proof_account: Account<'info, ProofAccount>,
     
     #[account(mut)]
-    pub authority: Signer<'info>,
+    pub user: Signer<'info>,
     
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(bump: u8)] 
-pub struct Deposit<'info> {
+pub struct StoreProof<'info> {
+    #[account(
+        mut,
+        seeds = [b"proof", owner.key().as_ref(), proof_account.order_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = proof_account.owner == owner.key() @ ErrorCode::Unauthorized
+    )]
+    pub proof_account: Account<'info, ProofAccount>,
+    
     #[account(mut)]
-    pub pool: Account<'info, Pool>,
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(order_id: u64)]
+pub struct SubmitOrder<'info> {
+    #[account(mut)]
+    pub order_book: Account<'info, DarkPoolOrderBook>,
+    
+    #[account(
+        seeds = [b"market", market_config.market_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub market_config: Account<'info, MarketConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"batch", market_config.open_batch_id.to_le_bytes().as_ref()],
+        bump = batch.bump
+    )]
+    pub batch: Account<'info, Batch>,
     
     #[account(
         init,
         payer = user,
-        space = 8 + 32 + 8 + 512 + 8 + 512 + 512 + 1 + 8,
-        seeds = [b"loan", user.key().as_ref()],
+        space = 8 + 32 + 32 + 8 + 4 + 512 + 32 + 8 + 1 + 8 + 1 + 8 + 8,
+        seeds = [b"order", user.key().as_ref(), order_id.to_le_bytes().as_ref()],
         bump
     )]
-    pub user_loan: Account<'info, UserLoan>,
+    pub order: Account<'info, EncryptedOrder>,
     
-    #[account(mut)]
-    pub user: Signer<'info>,
+    #[account(
+        seeds = [b"proof", user.key().as_ref(), order_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = proof_account.owner == user.key() @ ErrorCode::Unauthorized
+    )]
+    pub proof_account: Account<'info, ProofAccount>,
+    
+    #[account(
+        mut,
+        token::authority = user
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
     
-    /// CHECK: Token account validated by SPL token program
+    // Quote vault for bids, base vault for asks
     #[account(mut)]
-    pub user_token_account: AccountInfo<'info>,
+    pub escrow_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        seeds = [b"verifier", DARK_POOL_CIRCUIT.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub order_verifier: Account<'info, VerifierConfig>,
+    
+    #[account(
+        seeds = [b"verifier", COMPLIANCE_CIRCUIT.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub compliance_verifier: Account<'info, VerifierConfig>,
     
-    /// CHECK: Token account validated by SPL token program
     #[account(mut)]
-    pub pool_token_account: AccountInfo<'info>,
+    pub user: Signer<'info>,
     
     pub token_program: Program<'info, TokenProgram>,
     pub system_program: Program<'info, System>,
 }
 
 #[derive(Accounts)]
-#[instruction(bump: u8)]
-pub struct Borrow<'info> {
-    #[account(mut)]
-    pub pool: Account<'info, Pool>,
+pub struct CancelOrder<'info> {
+    #[account(
+        mut,
+        seeds = [b"batch", order.batch_id.to_le_bytes().as_ref()],
+        bump = batch.bump
+    )]
+    pub batch: Account<'info, Batch>,
+    
+    #[account(address = order.market @ ErrorCode::InvalidEscrow)]
+    pub market_config: Account<'info, MarketConfig>,
     
     #[account(
         mut,
-        seeds = [b"loan", user.key().as_ref()],
-        bump
+        seeds = [b"order", user.key().as_ref(), order.order_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = order.owner == user.key() @ ErrorCode::Unauthorized,
+        close = user
     )]
-    pub user_loan: Account<'info, UserLoan>,
+    pub order: Account<'info, EncryptedOrder>,
     
-    #[account(mut)]
-    pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"proof", user.key().as_ref(), order.order_id.to_le_bytes().as_ref()],
+        bump,
+        close = user
+    )]
+    pub proof_account: Account<'info, ProofAccount>,
+    
+    #[account(
+        mut,
+        token::authority = user
+    )]
+    pub user_token_account: Account<'info, TokenAccount>,
     
-    /// CHECK: Token account validated by SPL token program
     #[account(mut)]
-    pub user_token_account: AccountInfo<'info>,
+    pub escrow_vault: Account<'info, TokenAccount>,
     
-    /// CHECK: Token account validated by SPL token program
     #[account(mut)]
-    pub pool_token_account: AccountInfo<'info>,
+    pub user: Signer<'info>,
     
     pub token_program: Program<'info, TokenProgram>,
 }
 
+// The crank keeps the order account's rent; the larger proof account's rent
+// goes back to the owner.
 #[derive(Accounts)]
-#[instruction(bump: u8)]
-pub struct Liquidate<'info> {
-    #[account(mut)]
-    pub pool: Account<'info, Pool>,
+pub struct PruneExpiredOrder<'info> {
+    #[account(
+        mut,
+        seeds = [b"batch", order.batch_id.to_le_bytes().as_ref()],
+        bump = batch.bump
+    )]
+    pub batch: Account<'info, Batch>,
+    
+    #[account(address = order.market @ ErrorCode::InvalidEscrow)]
+    pub market_config: Account<'info, MarketConfig>,
     
     #[account(
         mut,
-        seeds = [b"loan", user_loan.owner.as_ref()],
-        bump
+        seeds = [b"order", order.owner.as_ref(), order.order_id.to_le_bytes().as_ref()],
+        bump,
+        close = crank
     )]
-    pub user_loan: Account<'info, UserLoan>,
+    pub order: Account<'info, EncryptedOrder>,
     
-    #[account(mut)]
-    pub liquidator: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"proof", order.owner.as_ref(), order.order_id.to_le_bytes().as_ref()],
+        bump,
+        close = owner
+    )]
+    pub proof_account: Account<'info, ProofAccount>,
+    
+    /// CHECK: Order owner, receives the refund and proof account rent
+    #[account(
+        mut,
+        address = order.owner @ ErrorCode::Unauthorized
+    )]
+    pub owner: UncheckedAccount<'info>,
+    
+    #[account(
+        mut,
+        token::authority = order.owner
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
     
-    /// CHECK: Token account validated by SPL token program
     #[account(mut)]
-    pub liquidator_token_account: AccountInfo<'info>,
+    pub escrow_vault: Account<'info, TokenAccount>,
     
-    /// CHECK: Token account validated by SPL token program
     #[account(mut)]
-    pub pool_token_account: AccountInfo<'info>,
+    pub crank: Signer<'info>,
     
     pub token_program: Program<'info, TokenProgram>,
 }
 
 #[derive(Accounts)]
-#[instruction(bump: u8)]
-pub struct InitializeDarkPool<'info> {
+pub struct SealBatch<'info> {
     #[account(
-        init,
-        payer = authority,
-        space = 8 + 32 + 8 + 8 + 1,
+        mut,
         seeds = [b"dark_pool"],
-        bump
+        bump = order_book.bump,
+        constraint = order_book.authority == authority.key() @ ErrorCode::Unauthorized
     )]
     pub order_book: Account<'info, DarkPoolOrderBook>,
     
-    #[account(mut)]
-    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"market", market_config.market_id.to_le_bytes().as_ref()],
+        bump = market_config.bump
+    )]
+    pub market_config: Account<'info, MarketConfig>,
+    
+    #[account(
+        mut,
+        seeds = [b"batch", market_config.open_batch_id.to_le_bytes().as_ref()],
+        bump = batch.bump
+    )]
+    pub batch: Account<'info, Batch>,
     
-    pub system_program: Program<'info, System>,
-}
-
-#[derive(Accounts)]
-#[instruction(order_id: u64)]
-pub struct CreateProofAccount<'info> {
     #[account(
         init,
-        payer = user,
-        space = 8 + 32 + 8 + 4 + 2048 + 4 + 2048,
-        seeds = [b"proof", user.key().as_ref(), order_id.to_le_bytes().as_ref()],
+        payer = authority,
+        space = 8 + 8 + 32 + 8 + 8 + 1 + 8 + 4 + 512 + 4 + 128 + 1,
+        seeds = [b"batch", order_book.next_batch_id.to_le_bytes().as_ref()],
         bump
     )]
-    pub proof_account: Account<'info, ProofAccount>,
+    pub next_batch: Account<'info, Batch>,
     
     #[account(mut)]
-    pub user: Signer<'info>,
+    pub authority: Signer<'info>,
     
     pub system_program: Program<'info, System>,
 }
 
+// Moves an order left behind by a settled batch into the market's open batch.
 #[derive(Accounts)]
-pub struct StoreProof<'info> {
+pub struct RequeueOrder<'info> {
+    #[account(
+        seeds = [b"batch", order.batch_id.to_le_bytes().as_ref()],
+        bump = batch.bump,
+        constraint = batch.status == BatchStatus::Settled @ ErrorCode::OrderNotInBatch
+    )]
+    pub batch: Account<'info, Batch>,
+    
+    #[account(address = order.market @ ErrorCode::InvalidEscrow)]
+    pub market_config: Account<'info, MarketConfig>,
+    
     #[account(
         mut,
-        seeds = [b"proof", owner.key().as_ref(), proof_account.order_id.to_le_bytes().as_ref()],
-        bump,
-        constraint = proof_account.owner == owner.key() @ ErrorCode::Unauthorized
+        seeds = [b"batch", market_config.open_batch_id.to_le_bytes().as_ref()],
+        bump = open_batch.bump
     )]
-    pub proof_account: Account<'info, ProofAccount>,
+    pub open_batch: Account<'info, Batch>,
     
-    #[account(mut)]
-    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"order", order.owner.as_ref(), order.order_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub order: Account<'info, EncryptedOrder>,
+    
+    pub crank: Signer<'info>,
 }
 
+// Every member of the sealed batch is passed in remaining accounts, in batch
+// order, as (order, payout token account, refund token account).
 #[derive(Accounts)]
-#[instruction(order_id: u64)]
-pub struct SubmitOrder<'info> {
-    #[account(mut)]
+pub struct BatchMatch<'info> {
+    #[account(
+        seeds = [b"dark_pool"],
+        bump = order_book.bump
+    )]
     pub order_book: Account<'info, DarkPoolOrderBook>,
     
     #[account(
-        init,
-        payer = user,
-        space = 8 + 32 + 8 + 4 + 512 + 32 + 8 + 1 + 8,
-        seeds = [b"order", user.key().as_ref(), order_id.to_le_bytes().as_ref()],
-        bump
+        mut,
+        seeds = [b"market", market_config.market_id.to_le_bytes().as_ref()],
+        bump = market_config.bump
     )]
-    pub order: Account<'info, EncryptedOrder>,
+    pub market_config: Account<'info, MarketConfig>,
     
     #[account(
-        seeds = [b"proof", user.key().as_ref(), order_id.to_le_bytes().as_ref()],
-        bump,
-        constraint = proof_account.owner == user.key() @ ErrorCode::Unauthorized
+        mut,
+        seeds = [b"batch", market_config.sealed_batch_id.to_le_bytes().as_ref()],
+        bump = batch.bump,
+        constraint = batch.market == market_config.key() @ ErrorCode::OrderNotInBatch
     )]
-    pub proof_account: Account<'info, ProofAccount>,
+    pub batch: Account<'info, Batch>,
     
-    #[account(mut)]
-    pub user: Signer<'info>,
+    // Open batch that unfilled orders roll over into
+    #[account(
+        mut,
+        seeds = [b"batch", market_config.open_batch_id.to_le_bytes().as_ref()],
+        bump = open_batch.bump
+    )]
+    pub open_batch: Account<'info, Batch>,
     
-    pub system_program: Program<'info, System>,
-}
-
-#[derive(Accounts)]
-pub struct BatchMatch<'info> {
-    #[account(mut)]
-    pub order_book: Account<'info, DarkPoolOrderBook>,
+    #[account(
+        mut,
+        address = market_config.base_vault @ ErrorCode::InvalidEscrow
+    )]
+    pub base_vault: Account<'info, TokenAccount>,
+    
+    #[account(
+        mut,
+        address = market_config.quote_vault @ ErrorCode::InvalidEscrow
+    )]
+    pub quote_vault: Account<'info, TokenAccount>,
     
     pub authority: Signer<'info>,
+    
+    pub token_program: Program<'info, TokenProgram>,
 }
 
 #[derive(Accounts)]
@@ -197,14 +1085,30 @@ pub struct StartAuction<'info> {
     #[account(
         init,
         payer = authority,
-        space = 8 + 32 + 8 + 8 + 8 + 8 + 8 + 1 + 32 + 8 + 1,
+        space = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8 + 1 + 32 + 8 + 1,
         seeds = [b"auction", auction_id.to_le_bytes().as_ref()],
         bump
     )]
     pub auction: Account<'info, LiquidationAuction>,
     
+    #[account(
+        mut,
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        mut,
+        seeds = [b"loan", pool.key().as_ref(), user_loan.owner.as_ref()],
+        bump
+    )]
     pub user_loan: Account<'info, UserLoan>,
     
+    /// CHECK: Price account validated by the oracle adapter
+    #[account(address = pool.oracle @ ErrorCode::InvalidOracle)]
+    pub oracle: AccountInfo<'info>,
+    
     #[account(mut)]
     pub authority: Signer<'info>,
     
@@ -214,41 +1118,104 @@ pub struct StartAuction<'info> {
 #[derive(Accounts)]
 #[instruction(auction_id: u64)]
 pub struct SubmitBid<'info> {
+    #[account(
+        seeds = [b"auction", auction_id.to_le_bytes().as_ref()],
+        bump = auction.bump
+    )]
     pub auction: Account<'info, LiquidationAuction>,
     
     #[account(
         init,
         payer = bidder,
-        space = 8 + 32 + 8 + 512 + 512 + 8,
+        space = 8 + 32 + 8 + 4 + 512 + 4 + 704 + 8,
         seeds = [b"bid", auction_id.to_le_bytes().as_ref(), bidder.key().as_ref()],
         bump
     )]
     pub bid: Account<'info, EncryptedBid>,
     
+    #[account(
+        seeds = [b"verifier", AUCTION_CIRCUIT.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub verifier_config: Account<'info, VerifierConfig>,
+    
     #[account(mut)]
     pub bidder: Signer<'info>,
     
     pub system_program: Program<'info, System>,
 }
 
+// The winner must have a recorded bid on the auction
 #[derive(Accounts)]
-#[instruction(bump: u8)]
 pub struct SettleAuction<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = auction.pool == pool.key() @ ErrorCode::Unauthorized
+    )]
     pub auction: Account<'info, LiquidationAuction>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump
+    )]
     pub pool: Account<'info, Pool>,
     
+    #[account(
+        mut,
+        seeds = [b"loan", pool.key().as_ref(), auction.position_owner.as_ref()],
+        bump,
+        constraint = user_loan.active_auction == auction.key() @ ErrorCode::Unauthorized
+    )]
+    pub user_loan: Account<'info, UserLoan>,
+    
+    #[account(
+        seeds = [b"bid", auction.auction_id.to_le_bytes().as_ref(), winner.key().as_ref()],
+        bump,
+        constraint = bid.bidder == winner.key() @ ErrorCode::Unauthorized
+    )]
+    pub bid: Account<'info, EncryptedBid>,
+    
     pub winner: Signer<'info>,
     
-    /// CHECK: Token account validated by SPL token program
-    #[account(mut)]
-    pub winner_token_account: AccountInfo<'info>,
+    #[account(
+        mut,
+        token::mint = pool.mint,
+        token::authority = winner
+    )]
+    pub winner_token_account: Account<'info, TokenAccount>,
     
-    /// CHECK: Token account validated by SPL token program
-    #[account(mut)]
-    pub pool_token_account: AccountInfo<'info>,
+    #[account(
+        mut,
+        address = pool.vault @ ErrorCode::InvalidVault
+    )]
+    pub pool_token_account: Account<'info, TokenAccount>,
     
     pub token_program: Program<'info, TokenProgram>,
-}
\ No newline at end of file
+}
+
+#[derive(Accounts)]
+pub struct CancelAuction<'info> {
+    #[account(
+        mut,
+        constraint = auction.pool == pool.key() @ ErrorCode::Unauthorized
+    )]
+    pub auction: Account<'info, LiquidationAuction>,
+    
+    #[account(
+        mut,
+        seeds = [b"pool", pool.mint.as_ref()],
+        bump = pool.bump
+    )]
+    pub pool: Account<'info, Pool>,
+    
+    #[account(
+        mut,
+        seeds = [b"loan", pool.key().as_ref(), auction.position_owner.as_ref()],
+        bump,
+        constraint = user_loan.active_auction == auction.key() @ ErrorCode::Unauthorized
+    )]
+    pub user_loan: Account<'info, UserLoan>,
+    
+    pub caller: Signer<'info>,
+}
@@ -5,7 +5,7 @@ pub enum ErrorCode {
     #[msg("Amount must be greater than zero")]
     InvalidAmount,
     
-    #[msg("Proof data cannot be empty")]
+    #[msg("Proof data is empty or malformed")]
     InvalidProof,
     
     #[msg("This position has already been liquidated")]
@@ -22,4 +22,130 @@ pub enum ErrorCode {
     
     #[msg("Cannot settle auction before expiration time")]
     AuctionNotExpired,
+    
+    #[msg("Loan already has a live liquidation auction")]
+    AuctionInProgress,
+    
+    #[msg("Bid is below the auction's minimum")]
+    BidBelowMinimum,
+    
+    #[msg("Verifying key is missing or malformed")]
+    InvalidVerifyingKey,
+    
+    #[msg("Unknown circuit type")]
+    InvalidCircuitType,
+    
+    #[msg("Proof failed verification")]
+    ProofVerificationFailed,
+    
+    #[msg("Proof was generated for a different circuit")]
+    CircuitMismatch,
+    
+    #[msg("Proof public inputs do not match on-chain state")]
+    PublicInputMismatch,
+    
+    #[msg("Withdrawal exceeds deposited collateral")]
+    InsufficientCollateral,
+    
+    #[msg("Interest rate model parameters are invalid")]
+    InvalidRateModel,
+    
+    #[msg("Token account is not the pool vault")]
+    InvalidVault,
+    
+    #[msg("Oracle account is not a valid price feed")]
+    InvalidOracle,
+    
+    #[msg("Oracle price is stale")]
+    StalePrice,
+    
+    #[msg("Oracle price confidence interval is too wide")]
+    PriceConfidenceTooWide,
+    
+    #[msg("Share mint does not match the pool")]
+    InvalidShareMint,
+    
+    #[msg("Pool does not have enough available liquidity")]
+    InsufficientLiquidity,
+    
+    #[msg("Borrow would exceed the pool's maximum LTV")]
+    ExceedsMaxLtv,
+    
+    #[msg("Position is healthy and cannot be liquidated")]
+    PositionHealthy,
+    
+    #[msg("Risk parameters are invalid")]
+    InvalidRiskParameters,
+    
+    #[msg("Treasury account does not match the pool")]
+    InvalidTreasury,
+    
+    #[msg("Delegate borrow exceeds the remaining allowance")]
+    AllowanceExceeded,
+    
+    #[msg("Loan still has debt or collateral and cannot be closed")]
+    LoanNotClosable,
+    
+    #[msg("Loan still has collateral or no residual debt")]
+    NoBadDebt,
+    
+    #[msg("Deposit would exceed the pool's deposit cap")]
+    DepositCapExceeded,
+    
+    #[msg("Borrow would exceed the pool's borrow cap")]
+    BorrowCapExceeded,
+    
+    #[msg("A flash loan is already outstanding on this pool")]
+    FlashLoanInProgress,
+    
+    #[msg("Flash borrow has no matching flash repay later in the transaction")]
+    MissingFlashRepay,
+    
+    #[msg("Flash loan must be called directly and repaid in full")]
+    InvalidFlashLoan,
+    
+    #[msg("Market bounds are invalid")]
+    InvalidMarketBounds,
+    
+    #[msg("Escrow vault or token account does not match the market")]
+    InvalidEscrow,
+    
+    #[msg("Escrow tier is above the maximum denomination")]
+    InvalidEscrowTier,
+    
+    #[msg("Order is not pending in this batch")]
+    OrderNotInBatch,
+    
+    #[msg("Batch has no room for more orders")]
+    BatchFull,
+    
+    #[msg("Batch has not been sealed for matching")]
+    BatchNotSealed,
+    
+    #[msg("Another batch is already sealed and awaiting settlement")]
+    BatchInProgress,
+    
+    #[msg("Order is locked in a sealed batch")]
+    OrderLocked,
+    
+    #[msg("Order expiry must be in the future")]
+    InvalidExpiry,
+    
+    #[msg("Order has expired and cannot be filled")]
+    OrderExpired,
+    
+    #[msg("Order has not expired yet")]
+    OrderNotExpired,
+    
+    #[msg("Batch fills and accounts do not line up")]
+    InvalidBatch,
+    
+    #[msg("Fill exceeds the order's escrowed funds")]
+    InsufficientEscrow,
+    
+    #[msg("Base bought and sold in the batch do not balance")]
+    UnbalancedBatch,
+    
+    #[msg("Clearing price is outside the market's price bounds")]
+    InvalidClearingPrice,
 }
\ No newline at end of file
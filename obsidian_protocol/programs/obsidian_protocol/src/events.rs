@@ -0,0 +1,315 @@
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct PoolInitialized {
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub share_mint: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct InterestRateModelUpdated {
+    pub pool: Pubkey,
+    pub base_rate_bps: u64,
+    pub slope1_bps: u64,
+    pub optimal_utilization_bps: u64,
+    pub slope2_bps: u64,
+}
+
+#[event]
+pub struct PoolKycRootUpdated {
+    pub pool: Pubkey,
+    pub kyc_registry_root: [u8; 32],
+}
+
+#[event]
+pub struct RiskParametersUpdated {
+    pub pool: Pubkey,
+    pub max_ltv_bps: u64,
+    pub liquidation_threshold_bps: u64,
+    pub close_factor_bps: u64,
+    pub liquidation_bonus_bps: u64,
+}
+
+#[event]
+pub struct PoolOracleUpdated {
+    pub pool: Pubkey,
+    pub oracle: Pubkey,
+}
+
+#[event]
+pub struct ReserveConfigUpdated {
+    pub pool: Pubkey,
+    pub reserve_factor_bps: u64,
+    pub origination_fee_bps: u64,
+    pub treasury: Pubkey,
+}
+
+#[event]
+pub struct PoolCapsUpdated {
+    pub pool: Pubkey,
+    pub deposit_cap: u64,
+    pub borrow_cap: u64,
+}
+
+#[event]
+pub struct FlashLoanFeeUpdated {
+    pub pool: Pubkey,
+    pub flash_loan_fee_bps: u64,
+}
+
+#[event]
+pub struct ReservesCollected {
+    pub pool: Pubkey,
+    pub treasury: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VerifyingKeyActivated {
+    pub verifier_config: Pubkey,
+    pub circuit_type: u64,
+    pub version: u64,
+}
+
+#[event]
+pub struct Deposited {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub collateral_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BorrowAllowanceSet {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub delegate: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct Supplied {
+    pub pool: Pubkey,
+    pub supplier: Pubkey,
+    pub amount: u64,
+    pub shares: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Redeemed {
+    pub pool: Pubkey,
+    pub supplier: Pubkey,
+    pub shares: u64,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FlashBorrowed {
+    pub pool: Pubkey,
+    pub borrower: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FlashRepaid {
+    pub pool: Pubkey,
+    pub borrower: Pubkey,
+    pub amount: u64,
+    pub fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Borrowed {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub borrower: Pubkey,
+    pub amount: u64,
+    pub origination_fee: u64,
+    pub borrowed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CollateralWithdrawn {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub collateral_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Repaid {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub payer: Pubkey,
+    pub amount: u64,
+    pub borrowed: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct Liquidated {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub liquidator: Pubkey,
+    pub repay_amount: u64,
+    pub seize_amount: u64,
+    pub fully_liquidated: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BadDebtWrittenOff {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub shortfall: u64,
+    pub covered_by_reserves: u64,
+    pub socialized: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct LoanClosed {
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MarketInitialized {
+    pub market: Pubkey,
+    pub market_id: u64,
+    pub authority: Pubkey,
+    pub min_order_size: u64,
+    pub max_order_size: u64,
+    pub min_price: u64,
+    pub max_price: u64,
+}
+
+#[event]
+pub struct MarketVaultsInitialized {
+    pub market: Pubkey,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub base_vault: Pubkey,
+    pub quote_vault: Pubkey,
+    pub base_lot_size: u64,
+    pub quote_lot_size: u64,
+    pub open_batch_id: u64,
+}
+
+#[event]
+pub struct MarketConfigUpdated {
+    pub market: Pubkey,
+    pub min_order_size: u64,
+    pub max_order_size: u64,
+    pub min_price: u64,
+    pub max_price: u64,
+    pub kyc_registry_root: [u8; 32],
+}
+
+#[event]
+pub struct OrderSubmitted {
+    pub market: Pubkey,
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    pub order_id: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OrderCancelled {
+    pub market: Pubkey,
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    pub order_id: u64,
+    pub refund_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OrderPruned {
+    pub market: Pubkey,
+    pub order: Pubkey,
+    pub owner: Pubkey,
+    pub crank: Pubkey,
+    pub order_id: u64,
+    pub refund_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct OrderRequeued {
+    pub market: Pubkey,
+    pub order: Pubkey,
+    pub from_batch_id: u64,
+    pub batch_id: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BatchSealed {
+    pub market: Pubkey,
+    pub batch_id: u64,
+    pub order_count: u32,
+    pub next_batch_id: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BatchMatched {
+    pub market: Pubkey,
+    pub batch_id: u64,
+    pub settlement_price: u64,
+    pub base_volume: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuctionStarted {
+    pub auction: Pubkey,
+    pub pool: Pubkey,
+    pub position_owner: Pubkey,
+    pub auction_id: u64,
+    pub collateral_amount: u64,
+    pub minimum_bid: u64,
+    pub end_time: i64,
+}
+
+#[event]
+pub struct BidSubmitted {
+    pub auction: Pubkey,
+    pub auction_id: u64,
+    pub bidder: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuctionSettled {
+    pub auction: Pubkey,
+    pub auction_id: u64,
+    pub winner: Pubkey,
+    pub winning_bid: u64,
+    pub collateral_amount: u64,
+    pub repay_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AuctionCancelled {
+    pub auction: Pubkey,
+    pub auction_id: u64,
+    pub position_owner: Pubkey,
+    pub collateral_amount: u64,
+    pub timestamp: i64,
+}
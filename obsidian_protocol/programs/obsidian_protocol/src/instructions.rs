@@ -1,7 +1,18 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Transfer};
+use anchor_lang::Discriminator;
+use anchor_lang::solana_program::instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT};
+use anchor_lang::solana_program::sysvar::instructions::{
+    load_current_index_checked, load_instruction_at_checked,
+};
+use anchor_spl::token::{self, Burn, MintTo, TokenAccount, Transfer};
+use crate::constants::*;
 use crate::contexts::*;
 use crate::errors::ErrorCode;
+use crate::events::*;
+use crate::interest;
+use crate::oracle;
+use crate::state::{Batch, BatchStatus, EncryptedOrder, LiquidationAuction, MarketConfig};
+use crate::verifier;
 
 // Helper function for encryption placeholder
 fn encrypt_proof(data: &[u8], owner: &Pubkey) -> Vec<u8> {
@@ -11,24 +22,524 @@ fn encrypt_proof(data: &[u8], owner: &Pubkey) -> Vec<u8> {
     encrypted
 }
 
+// Debt stays within `threshold_bps` of the collateral's oracle value
+fn within_threshold(
+    price: &oracle::OraclePrice,
+    collateral_amount: u64,
+    debt_amount: u64,
+    threshold_bps: u64,
+) -> Result<bool> {
+    let debt_value = price.value(debt_amount)?
+        .checked_mul(BPS_DENOMINATOR as u128)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let limit = price.value(collateral_amount)?
+        .checked_mul(threshold_bps as u128)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    Ok(debt_value <= limit)
+}
+
+// Amounts owed to an order filled for `fill` base units at `price`: its
+// payout, in base for bids and quote for asks, and the escrow refunded. Buyers
+// round their quote cost up and sellers round proceeds down, so the quote
+// vault never pays out more than buyers put in.
+fn settle_fill(is_bid: bool, fill: u64, price: u64, escrow_amount: u64) -> Result<(u64, u64)> {
+    let notional = (fill as u128)
+        .checked_mul(price as u128)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    if is_bid {
+        let cost = u64::try_from(notional.div_ceil(PRICE_SCALE))
+            .map_err(|_| ProgramError::ArithmeticOverflow)?;
+        require!(cost <= escrow_amount, ErrorCode::InsufficientEscrow);
+        Ok((fill, escrow_amount - cost))
+    } else {
+        require!(fill <= escrow_amount, ErrorCode::InsufficientEscrow);
+        let proceeds = u64::try_from(notional / PRICE_SCALE)
+            .map_err(|_| ProgramError::ArithmeticOverflow)?;
+        Ok((proceeds, escrow_amount - fill))
+    }
+}
+
+// Pays `amount` out of a market escrow vault, signed by the market PDA
+fn market_transfer<'info>(
+    token_program: &AccountInfo<'info>,
+    market_config: &Account<'info, MarketConfig>,
+    from: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let market_id = market_config.market_id.to_le_bytes();
+    let seeds = &[b"market".as_ref(), market_id.as_ref(), &[market_config.bump]];
+    let signer = &[&seeds[..]];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            Transfer {
+                from: from.clone(),
+                to: to.clone(),
+                authority: market_config.to_account_info(),
+            },
+            signer,
+        ),
+        amount,
+    )
+}
+
+fn open_batch(batch: &mut Batch, batch_id: u64, market: Pubkey, bump: u8) -> Result<()> {
+    batch.batch_id = batch_id;
+    batch.market = market;
+    batch.open_slot = Clock::get()?.slot;
+    batch.close_slot = 0;
+    batch.status = BatchStatus::Open;
+    batch.clearing_price = 0;
+    batch.orders = Vec::new();
+    batch.fills = Vec::new();
+    batch.bump = bump;
+    Ok(())
+}
+
+// Flash borrows must be followed by a flash_repay of the same amount against
+// the same pool later in the transaction.
+fn require_flash_repay(instructions: &AccountInfo, pool: &Pubkey, amount: u64) -> Result<()> {
+    let current_index = load_current_index_checked(instructions)? as usize;
+    let current = load_instruction_at_checked(current_index, instructions)?;
+    require!(
+        current.program_id == crate::ID && get_stack_height() == TRANSACTION_LEVEL_STACK_HEIGHT,
+        ErrorCode::InvalidFlashLoan
+    );
+    find_flash_repay(instructions, current_index + 1, pool, amount)
+}
+
+// Scans the transaction from `index` on for a flash_repay of `amount` to `pool`
+fn find_flash_repay(
+    instructions: &AccountInfo,
+    mut index: usize,
+    pool: &Pubkey,
+    amount: u64,
+) -> Result<()> {
+    let discriminator = crate::instruction::FlashRepay::DISCRIMINATOR;
+    while let Ok(ix) = load_instruction_at_checked(index, instructions) {
+        if ix.program_id == crate::ID
+            && ix.data.starts_with(discriminator)
+            && ix.accounts.first().map(|meta| &meta.pubkey) == Some(pool)
+        {
+            let args = &ix.data[discriminator.len()..];
+            require!(
+                args.len() >= U64_SIZE
+                    && u64::from_le_bytes(args[..U64_SIZE].try_into().unwrap()) == amount,
+                ErrorCode::InvalidFlashLoan
+            );
+            return Ok(());
+        }
+        index += 1;
+    }
+    err!(ErrorCode::MissingFlashRepay)
+}
+
+// Admin Instructions
+
+pub fn initialize_program_config(ctx: Context<InitializeProgramConfig>, bump: u8) -> Result<()> {
+    let program_config = &mut ctx.accounts.program_config;
+    program_config.admin = ctx.accounts.authority.key();
+    program_config.bump = bump;
+
+    msg!("Program config initialized with admin: {}", program_config.admin);
+    Ok(())
+}
+
+pub fn set_program_admin(ctx: Context<UpdateProgramConfig>, new_admin: Pubkey) -> Result<()> {
+    let program_config = &mut ctx.accounts.program_config;
+    program_config.admin = new_admin;
+
+    msg!("Program admin set to {}", new_admin);
+    Ok(())
+}
+
 // Lending Pool Instructions
 
 pub fn initialize_pool(ctx: Context<InitializePool>, bump: u8) -> Result<()> {
     let pool = &mut ctx.accounts.pool;
     pool.authority = ctx.accounts.authority.key();
     pool.bump = bump;
+    pool.mint = ctx.accounts.mint.key();
+    pool.vault = ctx.accounts.vault.key();
+    pool.share_mint = ctx.accounts.share_mint.key();
     pool.total_deposits = 0;
+    pool.total_shares = 0;
+    pool.total_collateral = 0;
     pool.total_borrowed = 0;
+    pool.kyc_registry_root = [0; 32];
+    pool.base_rate_bps = DEFAULT_BASE_RATE_BPS;
+    pool.slope1_bps = DEFAULT_SLOPE1_BPS;
+    pool.optimal_utilization_bps = DEFAULT_OPTIMAL_UTILIZATION_BPS;
+    pool.slope2_bps = DEFAULT_SLOPE2_BPS;
+    pool.borrow_index = INDEX_SCALE;
+    pool.last_accrual_timestamp = Clock::get()?.unix_timestamp;
+    pool.oracle = Pubkey::default();
+    pool.max_ltv_bps = DEFAULT_MAX_LTV_BPS;
+    pool.liquidation_threshold_bps = DEFAULT_LIQUIDATION_THRESHOLD_BPS;
+    pool.close_factor_bps = DEFAULT_CLOSE_FACTOR_BPS;
+    pool.liquidation_bonus_bps = DEFAULT_LIQUIDATION_BONUS_BPS;
+    pool.reserve_factor_bps = DEFAULT_RESERVE_FACTOR_BPS;
+    pool.origination_fee_bps = DEFAULT_ORIGINATION_FEE_BPS;
+    pool.protocol_reserves = 0;
+    pool.treasury = Pubkey::default();
+    pool.flash_loan_fee_bps = DEFAULT_FLASH_LOAN_FEE_BPS;
+    pool.flash_loan_amount = 0;
+    pool.deposit_cap = u64::MAX;
+    pool.borrow_cap = u64::MAX;
+
+    emit!(PoolInitialized {
+        pool: pool.key(),
+        mint: pool.mint,
+        vault: pool.vault,
+        share_mint: pool.share_mint,
+        authority: pool.authority,
+    });
+
+    msg!("Pool for mint {} initialized by: {}", pool.mint, pool.authority);
+    Ok(())
+}
+
+pub fn set_interest_rate_model(
+    ctx: Context<UpdatePool>,
+    base_rate_bps: u64,
+    slope1_bps: u64,
+    optimal_utilization_bps: u64,
+    slope2_bps: u64,
+) -> Result<()> {
+    require!(
+        optimal_utilization_bps > 0 && optimal_utilization_bps < BPS_DENOMINATOR,
+        ErrorCode::InvalidRateModel
+    );
+
+    let pool = &mut ctx.accounts.pool;
+    interest::accrue_interest(pool, Clock::get()?.unix_timestamp)?;
+    pool.base_rate_bps = base_rate_bps;
+    pool.slope1_bps = slope1_bps;
+    pool.optimal_utilization_bps = optimal_utilization_bps;
+    pool.slope2_bps = slope2_bps;
+
+    emit!(InterestRateModelUpdated {
+        pool: pool.key(),
+        base_rate_bps,
+        slope1_bps,
+        optimal_utilization_bps,
+        slope2_bps,
+    });
+
+    msg!(
+        "Rate model updated: borrow {} bps, supply {} bps",
+        interest::borrow_rate_bps(pool)?,
+        interest::supply_rate_bps(pool)?
+    );
+    Ok(())
+}
 
-    msg!("Pool initialized by: {}", pool.authority);
+pub fn set_kyc_registry_root(ctx: Context<UpdatePool>, kyc_registry_root: [u8; 32]) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    pool.kyc_registry_root = kyc_registry_root;
+
+    emit!(PoolKycRootUpdated {
+        pool: pool.key(),
+        kyc_registry_root,
+    });
+
+    msg!("Pool KYC registry root updated");
+    Ok(())
+}
+
+pub fn set_risk_parameters(
+    ctx: Context<UpdatePool>,
+    max_ltv_bps: u64,
+    liquidation_threshold_bps: u64,
+    close_factor_bps: u64,
+    liquidation_bonus_bps: u64,
+) -> Result<()> {
+    require!(
+        max_ltv_bps > 0
+            && max_ltv_bps < liquidation_threshold_bps
+            && liquidation_threshold_bps <= BPS_DENOMINATOR,
+        ErrorCode::InvalidRiskParameters
+    );
+    require!(
+        close_factor_bps > 0
+            && close_factor_bps <= BPS_DENOMINATOR
+            && liquidation_bonus_bps < BPS_DENOMINATOR,
+        ErrorCode::InvalidRiskParameters
+    );
+
+    let pool = &mut ctx.accounts.pool;
+    pool.max_ltv_bps = max_ltv_bps;
+    pool.liquidation_threshold_bps = liquidation_threshold_bps;
+    pool.close_factor_bps = close_factor_bps;
+    pool.liquidation_bonus_bps = liquidation_bonus_bps;
+
+    emit!(RiskParametersUpdated {
+        pool: pool.key(),
+        max_ltv_bps,
+        liquidation_threshold_bps,
+        close_factor_bps,
+        liquidation_bonus_bps,
+    });
+
+    msg!(
+        "Risk parameters updated: max LTV {} bps, liquidation threshold {} bps",
+        max_ltv_bps,
+        liquidation_threshold_bps
+    );
+    Ok(())
+}
+
+pub fn set_pool_oracle(ctx: Context<UpdatePool>, oracle: Pubkey) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    pool.oracle = oracle;
+
+    emit!(PoolOracleUpdated {
+        pool: pool.key(),
+        oracle,
+    });
+
+    msg!("Pool oracle set to {}", oracle);
+    Ok(())
+}
+
+pub fn set_reserve_config(
+    ctx: Context<UpdatePool>,
+    reserve_factor_bps: u64,
+    origination_fee_bps: u64,
+    treasury: Pubkey,
+) -> Result<()> {
+    require!(
+        reserve_factor_bps <= BPS_DENOMINATOR && origination_fee_bps < BPS_DENOMINATOR,
+        ErrorCode::InvalidRiskParameters
+    );
+
+    let pool = &mut ctx.accounts.pool;
+    interest::accrue_interest(pool, Clock::get()?.unix_timestamp)?;
+    pool.reserve_factor_bps = reserve_factor_bps;
+    pool.origination_fee_bps = origination_fee_bps;
+    pool.treasury = treasury;
+
+    emit!(ReserveConfigUpdated {
+        pool: pool.key(),
+        reserve_factor_bps,
+        origination_fee_bps,
+        treasury,
+    });
+
+    msg!(
+        "Reserve config updated: reserve factor {} bps, origination fee {} bps",
+        reserve_factor_bps,
+        origination_fee_bps
+    );
+    Ok(())
+}
+
+pub fn set_pool_caps(ctx: Context<UpdatePool>, deposit_cap: u64, borrow_cap: u64) -> Result<()> {
+    let pool = &mut ctx.accounts.pool;
+    pool.deposit_cap = deposit_cap;
+    pool.borrow_cap = borrow_cap;
+
+    emit!(PoolCapsUpdated {
+        pool: pool.key(),
+        deposit_cap,
+        borrow_cap,
+    });
+
+    msg!("Pool caps updated: deposits {}, borrows {}", deposit_cap, borrow_cap);
+    Ok(())
+}
+
+pub fn set_flash_loan_fee(ctx: Context<UpdatePool>, flash_loan_fee_bps: u64) -> Result<()> {
+    require!(flash_loan_fee_bps < BPS_DENOMINATOR, ErrorCode::InvalidRiskParameters);
+
+    let pool = &mut ctx.accounts.pool;
+    pool.flash_loan_fee_bps = flash_loan_fee_bps;
+
+    emit!(FlashLoanFeeUpdated {
+        pool: pool.key(),
+        flash_loan_fee_bps,
+    });
+
+    msg!("Flash loan fee set to {} bps", flash_loan_fee_bps);
+    Ok(())
+}
+
+pub fn collect_reserves(ctx: Context<CollectReserves>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    interest::accrue_interest(&mut ctx.accounts.pool, now)?;
+
+    // Reserves still owed by borrowers stay on the books until repaid.
+    let amount = ctx.accounts.pool.protocol_reserves.min(interest::pool_cash(&ctx.accounts.pool));
+    require!(amount > 0, ErrorCode::InvalidAmount);
+
+    let pool = &ctx.accounts.pool;
+    let seeds = &[b"pool".as_ref(), pool.mint.as_ref(), &[pool.bump]];
+    let signer = &[&seeds[..]];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.pool_token_account.to_account_info(),
+                to: ctx.accounts.treasury.to_account_info(),
+                authority: ctx.accounts.pool.to_account_info(),
+            },
+            signer,
+        ),
+        amount,
+    )?;
+
+    let pool = &mut ctx.accounts.pool;
+    pool.protocol_reserves = pool.protocol_reserves
+        .checked_sub(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    emit!(ReservesCollected {
+        pool: pool.key(),
+        treasury: pool.treasury,
+        amount,
+        timestamp: now,
+    });
+
+    msg!("Collected {} tokens of protocol reserves to {}", amount, pool.treasury);
+    Ok(())
+}
+
+pub fn initialize_price_feed(ctx: Context<InitializePriceFeed>, expo: i32, bump: u8) -> Result<()> {
+    let price_feed = &mut ctx.accounts.price_feed;
+    price_feed.authority = ctx.accounts.authority.key();
+    price_feed.mint = ctx.accounts.mint.key();
+    price_feed.price = 0;
+    price_feed.expo = expo;
+    price_feed.conf = 0;
+    price_feed.publish_time = 0;
+    price_feed.bump = bump;
+
+    msg!("Price feed initialized for mint {}", price_feed.mint);
+    Ok(())
+}
+
+pub fn update_price_feed(ctx: Context<UpdatePriceFeed>, price: i64, conf: u64) -> Result<()> {
+    require!(price > 0, ErrorCode::InvalidAmount);
+
+    let price_feed = &mut ctx.accounts.price_feed;
+    price_feed.price = price;
+    price_feed.conf = conf;
+    price_feed.publish_time = Clock::get()?.unix_timestamp;
+
+    msg!("Price feed for {} updated to {}", price_feed.mint, price);
+    Ok(())
+}
+
+// Verifier Instructions
+
+pub fn initialize_verifier(
+    ctx: Context<InitializeVerifier>,
+    circuit_type: u64,
+    bump: u8,
+) -> Result<()> {
+    require!(
+        (COMPLIANCE_CIRCUIT..=LIQUIDATION_CIRCUIT).contains(&circuit_type),
+        ErrorCode::InvalidCircuitType
+    );
+
+    let verifier_config = &mut ctx.accounts.verifier_config;
+    verifier_config.authority = ctx.accounts.authority.key();
+    verifier_config.circuit_type = circuit_type;
+    verifier_config.version = 0;
+    verifier_config.verifying_key = Vec::new();
+    verifier_config.pending_key = Vec::new();
+    verifier_config.bump = bump;
+
+    msg!("Verifier for circuit {} initialized by: {}", circuit_type, verifier_config.authority);
+    Ok(())
+}
+
+pub fn store_verifying_key(ctx: Context<UpdateVerifier>, chunk: Vec<u8>) -> Result<()> {
+    let verifier_config = &mut ctx.accounts.verifier_config;
+    require!(
+        verifier_config.pending_key.len() + chunk.len() <= VERIFYING_KEY_SIZE,
+        ErrorCode::InvalidVerifyingKey
+    );
+    verifier_config.pending_key.extend_from_slice(&chunk);
+
+    msg!("Stored {} byte verifying key chunk", chunk.len());
+    Ok(())
+}
+
+pub fn activate_verifying_key(ctx: Context<UpdateVerifier>) -> Result<()> {
+    let verifier_config = &mut ctx.accounts.verifier_config;
+    require!(
+        verifier_config.pending_key.len() == VERIFYING_KEY_SIZE,
+        ErrorCode::InvalidVerifyingKey
+    );
+
+    verifier_config.verifying_key = std::mem::take(&mut verifier_config.pending_key);
+    verifier_config.version = verifier_config.version
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    emit!(VerifyingKeyActivated {
+        verifier_config: verifier_config.key(),
+        circuit_type: verifier_config.circuit_type,
+        version: verifier_config.version,
+    });
+
+    msg!(
+        "Verifying key v{} activated for circuit {}",
+        verifier_config.version,
+        verifier_config.circuit_type
+    );
     Ok(())
 }
 
 pub fn deposit(ctx: Context<Deposit>, amount: u64, proof_data: Vec<u8>) -> Result<()> {
     require!(amount > 0, ErrorCode::InvalidAmount);
-    require!(!proof_data.is_empty(), ErrorCode::InvalidProof);
+
+    let inputs = verifier::verify_proof(
+        &ctx.accounts.verifier_config,
+        &proof_data,
+        &ctx.accounts.user.key(),
+    )?;
+    require!(
+        inputs.kyc_registry_root == ctx.accounts.pool.kyc_registry_root,
+        ErrorCode::PublicInputMismatch
+    );
 
     let encrypted_proof = encrypt_proof(&proof_data, &ctx.accounts.user.key());
+    let now = Clock::get()?.unix_timestamp;
+    interest::accrue_interest(&mut ctx.accounts.pool, now)?;
+
+    let user_loan = &mut ctx.accounts.user_loan;
+    if user_loan.owner == Pubkey::default() {
+        user_loan.owner = ctx.accounts.user.key();
+        user_loan.pool = ctx.accounts.pool.key();
+        user_loan.collateral_amount = 0;
+        user_loan.borrowed = 0;
+        user_loan.liquidated = false;
+        user_loan.deposit_timestamp = now;
+        user_loan.ltv_proof = Vec::new();
+        user_loan.liquidation_proof = Vec::new();
+        user_loan.active_auction = Pubkey::default();
+    }
+    require!(!user_loan.liquidated, ErrorCode::PositionLiquidated);
+    require!(
+        interest::deposit_exposure(&ctx.accounts.pool)
+            .checked_add(amount)
+            .is_some_and(|total| total <= ctx.accounts.pool.deposit_cap),
+        ErrorCode::DepositCapExceeded
+    );
+    interest::sync_loan(user_loan, &ctx.accounts.pool)?;
+
+    user_loan.collateral_amount = user_loan.collateral_amount
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    user_loan.collateral_encrypted = encrypted_proof;
 
     token::transfer(
         CpiContext::new(
@@ -42,40 +553,323 @@ pub fn deposit(ctx: Context<Deposit>, amount: u64, proof_data: Vec<u8>) -> Resul
         amount,
     )?;
 
-    let user_loan = &mut ctx.accounts.user_loan;
-    user_loan.owner = ctx.accounts.user.key();
-    user_loan.collateral_amount = amount;
-    user_loan.collateral_encrypted = encrypted_proof;
-    user_loan.borrowed = 0;
-    user_loan.liquidated = false;
-    user_loan.deposit_timestamp = Clock::get()?.unix_timestamp;
-    user_loan.ltv_proof = Vec::new();
-    user_loan.liquidation_proof = Vec::new();
+    let pool = &mut ctx.accounts.pool;
+    pool.total_collateral = pool.total_collateral
+        .checked_add(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    emit!(Deposited {
+        pool: pool.key(),
+        owner: ctx.accounts.user_loan.owner,
+        amount,
+        collateral_amount: ctx.accounts.user_loan.collateral_amount,
+        timestamp: now,
+    });
+
+    msg!("Deposited {} tokens with encrypted proof", amount);
+    Ok(())
+}
+
+pub fn supply(ctx: Context<Supply>, amount: u64) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+
+    let now = Clock::get()?.unix_timestamp;
+    interest::accrue_interest(&mut ctx.accounts.pool, now)?;
+    require!(
+        interest::deposit_exposure(&ctx.accounts.pool)
+            .checked_add(amount)
+            .is_some_and(|total| total <= ctx.accounts.pool.deposit_cap),
+        ErrorCode::DepositCapExceeded
+    );
+    let shares = interest::shares_for_amount(&ctx.accounts.pool, amount)?;
+    require!(shares > 0, ErrorCode::InvalidAmount);
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.pool_token_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let pool = &ctx.accounts.pool;
+    let seeds = &[b"pool".as_ref(), pool.mint.as_ref(), &[pool.bump]];
+    let signer = &[&seeds[..]];
+
+    token::mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.share_mint.to_account_info(),
+                to: ctx.accounts.user_share_account.to_account_info(),
+                authority: ctx.accounts.pool.to_account_info(),
+            },
+            signer,
+        ),
+        shares,
+    )?;
 
     let pool = &mut ctx.accounts.pool;
     pool.total_deposits = pool.total_deposits
         .checked_add(amount)
         .ok_or(ProgramError::ArithmeticOverflow)?;
+    pool.total_shares = pool.total_shares
+        .checked_add(shares)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
 
-    msg!("Deposited {} tokens with encrypted proof", amount);
+    emit!(Supplied {
+        pool: pool.key(),
+        supplier: ctx.accounts.user.key(),
+        amount,
+        shares,
+        timestamp: now,
+    });
+
+    msg!("Supplied {} tokens for {} shares", amount, shares);
+    Ok(())
+}
+
+pub fn redeem(ctx: Context<Redeem>, shares: u64) -> Result<()> {
+    require!(shares > 0, ErrorCode::InvalidAmount);
+
+    let now = Clock::get()?.unix_timestamp;
+    interest::accrue_interest(&mut ctx.accounts.pool, now)?;
+    let amount = interest::amount_for_shares(&ctx.accounts.pool, shares)?;
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    require!(
+        interest::available_liquidity(&ctx.accounts.pool) >= amount,
+        ErrorCode::InsufficientLiquidity
+    );
+
+    token::burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.share_mint.to_account_info(),
+                from: ctx.accounts.user_share_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        shares,
+    )?;
+
+    let pool = &ctx.accounts.pool;
+    let seeds = &[b"pool".as_ref(), pool.mint.as_ref(), &[pool.bump]];
+    let signer = &[&seeds[..]];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.pool_token_account.to_account_info(),
+                to: ctx.accounts.user_token_account.to_account_info(),
+                authority: ctx.accounts.pool.to_account_info(),
+            },
+            signer,
+        ),
+        amount,
+    )?;
+
+    let pool = &mut ctx.accounts.pool;
+    pool.total_deposits = pool.total_deposits
+        .checked_sub(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    pool.total_shares = pool.total_shares
+        .checked_sub(shares)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    emit!(Redeemed {
+        pool: pool.key(),
+        supplier: ctx.accounts.user.key(),
+        shares,
+        amount,
+        timestamp: now,
+    });
+
+    msg!("Redeemed {} shares for {} tokens", shares, amount);
+    Ok(())
+}
+
+pub fn set_borrow_allowance(
+    ctx: Context<SetBorrowAllowance>,
+    amount: u64,
+    bump: u8,
+) -> Result<()> {
+    let borrow_allowance = &mut ctx.accounts.borrow_allowance;
+    borrow_allowance.owner = ctx.accounts.owner.key();
+    borrow_allowance.delegate = ctx.accounts.delegate.key();
+    borrow_allowance.pool = ctx.accounts.pool.key();
+    borrow_allowance.remaining = amount;
+    borrow_allowance.bump = bump;
+
+    emit!(BorrowAllowanceSet {
+        pool: borrow_allowance.pool,
+        owner: borrow_allowance.owner,
+        delegate: borrow_allowance.delegate,
+        amount,
+    });
+
+    msg!(
+        "Borrow allowance for {} set to {}",
+        borrow_allowance.delegate,
+        amount
+    );
+    Ok(())
+}
+
+pub fn flash_borrow(ctx: Context<FlashBorrow>, amount: u64) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    require!(ctx.accounts.pool.flash_loan_amount == 0, ErrorCode::FlashLoanInProgress);
+
+    let now = Clock::get()?.unix_timestamp;
+    interest::accrue_interest(&mut ctx.accounts.pool, now)?;
+    require!(
+        interest::available_liquidity(&ctx.accounts.pool) >= amount,
+        ErrorCode::InsufficientLiquidity
+    );
+    require_flash_repay(&ctx.accounts.instructions, &ctx.accounts.pool.key(), amount)?;
+
+    let pool = &ctx.accounts.pool;
+    let seeds = &[b"pool".as_ref(), pool.mint.as_ref(), &[pool.bump]];
+    let signer = &[&seeds[..]];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.pool_token_account.to_account_info(),
+                to: ctx.accounts.user_token_account.to_account_info(),
+                authority: ctx.accounts.pool.to_account_info(),
+            },
+            signer,
+        ),
+        amount,
+    )?;
+
+    ctx.accounts.pool.flash_loan_amount = amount;
+
+    emit!(FlashBorrowed {
+        pool: ctx.accounts.pool.key(),
+        borrower: ctx.accounts.user.key(),
+        amount,
+        timestamp: now,
+    });
+
+    msg!("Flash borrowed {} tokens", amount);
+    Ok(())
+}
+
+pub fn flash_repay(ctx: Context<FlashRepay>, amount: u64) -> Result<()> {
+    require!(
+        amount > 0 && ctx.accounts.pool.flash_loan_amount == amount,
+        ErrorCode::InvalidFlashLoan
+    );
+
+    let fee = interest::flash_loan_fee(&ctx.accounts.pool, amount);
+    let repay_amount = amount
+        .checked_add(fee)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.pool_token_account.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        repay_amount,
+    )?;
+
+    let pool = &mut ctx.accounts.pool;
+    interest::close_flash_loan(pool, fee)?;
+
+    emit!(FlashRepaid {
+        pool: pool.key(),
+        borrower: ctx.accounts.user.key(),
+        amount,
+        fee,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Flash repaid {} tokens with {} fee", amount, fee);
     Ok(())
 }
 
 pub fn borrow(ctx: Context<Borrow>, borrow_amount: u64, ltv_proof: Vec<u8>) -> Result<()> {
     require!(borrow_amount > 0, ErrorCode::InvalidAmount);
-    require!(!ltv_proof.is_empty(), ErrorCode::InvalidProof);
+
+    let now = Clock::get()?.unix_timestamp;
+    let price = oracle::load_price(&ctx.accounts.oracle, now)?;
+    interest::accrue_interest(&mut ctx.accounts.pool, now)?;
+
+    require!(
+        interest::available_liquidity(&ctx.accounts.pool) >= borrow_amount,
+        ErrorCode::InsufficientLiquidity
+    );
 
     let user_loan = &mut ctx.accounts.user_loan;
     require!(!user_loan.liquidated, ErrorCode::PositionLiquidated);
+    interest::sync_loan(user_loan, &ctx.accounts.pool)?;
 
-    let encrypted_ltv = encrypt_proof(&ltv_proof, &ctx.accounts.user.key());
-    user_loan.borrowed = user_loan.borrowed
+    // The origination fee is added to the debt rather than withheld from the payout.
+    let origination_fee = ((borrow_amount as u128)
+        * ctx.accounts.pool.origination_fee_bps as u128)
+        .div_ceil(BPS_DENOMINATOR as u128) as u64;
+    let new_borrowed = user_loan.borrowed
         .checked_add(borrow_amount)
+        .and_then(|b| b.checked_add(origination_fee))
         .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    // Delegates draw down their allowance; the debt stays on the owner's loan.
+    let debt_increase = borrow_amount + origination_fee;
+    if user_loan.owner != ctx.accounts.user.key() {
+        let allowance = ctx.accounts.borrow_allowance
+            .as_mut()
+            .ok_or(ErrorCode::Unauthorized)?;
+        allowance.remaining = allowance.remaining
+            .checked_sub(debt_increase)
+            .ok_or(ErrorCode::AllowanceExceeded)?;
+    }
+
+    let pool = &ctx.accounts.pool;
+    require!(
+        pool.total_borrowed
+            .checked_add(borrow_amount)
+            .and_then(|b| b.checked_add(origination_fee))
+            .is_some_and(|total| total <= pool.borrow_cap),
+        ErrorCode::BorrowCapExceeded
+    );
+
+    let max_ltv_bps = ctx.accounts.pool.max_ltv_bps;
+    require!(
+        within_threshold(&price, user_loan.collateral_amount, new_borrowed, max_ltv_bps)?,
+        ErrorCode::ExceedsMaxLtv
+    );
+
+    let inputs = verifier::verify_proof(
+        &ctx.accounts.verifier_config,
+        &ltv_proof,
+        &ctx.accounts.user.key(),
+    )?;
+    require!(
+        inputs.loan_collateral <= user_loan.collateral_amount
+            && inputs.loan_debt >= new_borrowed
+            && inputs.ltv_bps == max_ltv_bps,
+        ErrorCode::PublicInputMismatch
+    );
+
+    let encrypted_ltv = encrypt_proof(&ltv_proof, &ctx.accounts.user.key());
+    user_loan.borrowed = new_borrowed;
     user_loan.ltv_proof = encrypted_ltv;
 
     let pool = &ctx.accounts.pool;
-    let seeds = &[b"pool".as_ref(), &[pool.bump]];
+    let seeds = &[b"pool".as_ref(), pool.mint.as_ref(), &[pool.bump]];
     let signer = &[&seeds[..]];
 
     token::transfer(
@@ -94,40 +888,332 @@ pub fn borrow(ctx: Context<Borrow>, borrow_amount: u64, ltv_proof: Vec<u8>) -> R
     let pool = &mut ctx.accounts.pool;
     pool.total_borrowed = pool.total_borrowed
         .checked_add(borrow_amount)
+        .and_then(|b| b.checked_add(origination_fee))
         .ok_or(ProgramError::ArithmeticOverflow)?;
+    interest::credit_revenue(pool, origination_fee)?;
 
-    msg!("Borrowed {} tokens with LTV verification", borrow_amount);
+    emit!(Borrowed {
+        pool: pool.key(),
+        owner: ctx.accounts.user_loan.owner,
+        borrower: ctx.accounts.user.key(),
+        amount: borrow_amount,
+        origination_fee,
+        borrowed: new_borrowed,
+        timestamp: now,
+    });
+
+    msg!(
+        "Borrowed {} tokens with LTV verification, debt value {}",
+        borrow_amount,
+        price.value(new_borrowed)?
+    );
     Ok(())
 }
 
-pub fn liquidate(ctx: Context<Liquidate>, liquidation_proof: Vec<u8>) -> Result<()> {
-    require!(!liquidation_proof.is_empty(), ErrorCode::InvalidProof);
+pub fn withdraw_collateral(
+    ctx: Context<WithdrawCollateral>,
+    amount: u64,
+    ltv_proof: Vec<u8>,
+) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidAmount);
+
+    let now = Clock::get()?.unix_timestamp;
+    interest::accrue_interest(&mut ctx.accounts.pool, now)?;
+
+    let user_loan = &mut ctx.accounts.user_loan;
+    require!(!user_loan.liquidated, ErrorCode::PositionLiquidated);
+    interest::sync_loan(user_loan, &ctx.accounts.pool)?;
+
+    let remaining_collateral = user_loan.collateral_amount
+        .checked_sub(amount)
+        .ok_or(ErrorCode::InsufficientCollateral)?;
+
+    if user_loan.borrowed > 0 {
+        let price = oracle::load_price(&ctx.accounts.oracle, now)?;
+        require!(
+            within_threshold(
+                &price,
+                remaining_collateral,
+                user_loan.borrowed,
+                ctx.accounts.pool.max_ltv_bps
+            )?,
+            ErrorCode::ExceedsMaxLtv
+        );
+
+        let inputs = verifier::verify_proof(
+            &ctx.accounts.verifier_config,
+            &ltv_proof,
+            &ctx.accounts.user.key(),
+        )?;
+        require!(
+            inputs.loan_collateral <= remaining_collateral
+                && inputs.loan_debt >= user_loan.borrowed
+                && inputs.ltv_bps == ctx.accounts.pool.max_ltv_bps,
+            ErrorCode::PublicInputMismatch
+        );
+        user_loan.ltv_proof = encrypt_proof(&ltv_proof, &ctx.accounts.user.key());
+    }
+    user_loan.collateral_amount = remaining_collateral;
+
+    let pool = &ctx.accounts.pool;
+    let seeds = &[b"pool".as_ref(), pool.mint.as_ref(), &[pool.bump]];
+    let signer = &[&seeds[..]];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.pool_token_account.to_account_info(),
+                to: ctx.accounts.user_token_account.to_account_info(),
+                authority: ctx.accounts.pool.to_account_info(),
+            },
+            signer,
+        ),
+        amount,
+    )?;
+
+    let pool = &mut ctx.accounts.pool;
+    pool.total_collateral = pool.total_collateral
+        .checked_sub(amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    emit!(CollateralWithdrawn {
+        pool: pool.key(),
+        owner: ctx.accounts.user_loan.owner,
+        amount,
+        collateral_amount: ctx.accounts.user_loan.collateral_amount,
+        timestamp: now,
+    });
+
+    msg!("Withdrew {} tokens of collateral", amount);
+    Ok(())
+}
+
+pub fn repay(ctx: Context<Repay>, amount: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    interest::accrue_interest(&mut ctx.accounts.pool, now)?;
+
+    let user_loan = &mut ctx.accounts.user_loan;
+    interest::sync_loan(user_loan, &ctx.accounts.pool)?;
+    let repay_amount = amount.min(user_loan.borrowed);
+    require!(repay_amount > 0, ErrorCode::InvalidAmount);
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.payer_token_account.to_account_info(),
+                to: ctx.accounts.pool_token_account.to_account_info(),
+                authority: ctx.accounts.payer.to_account_info(),
+            },
+        ),
+        repay_amount,
+    )?;
+
+    user_loan.borrowed = user_loan.borrowed
+        .checked_sub(repay_amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    interest::repay_borrowed(&mut ctx.accounts.pool, repay_amount)?;
+
+    emit!(Repaid {
+        pool: ctx.accounts.pool.key(),
+        owner: user_loan.owner,
+        payer: ctx.accounts.payer.key(),
+        amount: repay_amount,
+        borrowed: user_loan.borrowed,
+        timestamp: now,
+    });
+
+    msg!("Repaid {} tokens for {}", repay_amount, user_loan.owner);
+    Ok(())
+}
+
+pub fn liquidate(
+    ctx: Context<Liquidate>,
+    repay_amount: u64,
+    liquidation_proof: Vec<u8>,
+) -> Result<()> {
+    require!(repay_amount > 0, ErrorCode::InvalidAmount);
+
+    let now = Clock::get()?.unix_timestamp;
+    let price = oracle::load_price(&ctx.accounts.oracle, now)?;
+    interest::accrue_interest(&mut ctx.accounts.pool, now)?;
 
     let user_loan = &mut ctx.accounts.user_loan;
     require!(!user_loan.liquidated, ErrorCode::PositionLiquidated);
+    // Collateral under auction has left collateral_amount but the debt it
+    // covers hasn't, so the loan only looks unhealthy until the auction ends.
+    require!(
+        user_loan.active_auction == Pubkey::default(),
+        ErrorCode::AuctionInProgress
+    );
+    interest::sync_loan(user_loan, &ctx.accounts.pool)?;
+
+    let liquidation_threshold_bps = ctx.accounts.pool.liquidation_threshold_bps;
+    require!(
+        !within_threshold(
+            &price,
+            user_loan.collateral_amount,
+            user_loan.borrowed,
+            liquidation_threshold_bps
+        )?,
+        ErrorCode::PositionHealthy
+    );
+
+    let inputs = verifier::verify_proof(
+        &ctx.accounts.verifier_config,
+        &liquidation_proof,
+        &ctx.accounts.liquidator.key(),
+    )?;
+    require!(
+        inputs.loan_collateral >= user_loan.collateral_amount
+            && inputs.loan_debt <= user_loan.borrowed
+            && inputs.ltv_bps == liquidation_threshold_bps,
+        ErrorCode::PublicInputMismatch
+    );
+
+    // Liquidator repays at most close_factor of the debt and seizes the
+    // equivalent collateral plus the liquidation bonus.
+    let pool = &ctx.accounts.pool;
+    let max_repay = (user_loan.borrowed as u128 * pool.close_factor_bps as u128
+        / BPS_DENOMINATOR as u128) as u64;
+    let repay_amount = repay_amount.min(max_repay.max(1)).min(user_loan.borrowed);
+    require!(repay_amount > 0, ErrorCode::InvalidAmount);
+
+    let seize_amount = ((repay_amount as u128)
+        * (BPS_DENOMINATOR + pool.liquidation_bonus_bps) as u128
+        / BPS_DENOMINATOR as u128)
+        .min(user_loan.collateral_amount as u128) as u64;
+
+    user_loan.borrowed = user_loan.borrowed
+        .checked_sub(repay_amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    user_loan.collateral_amount = user_loan.collateral_amount
+        .checked_sub(seize_amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    user_loan.liquidation_proof = encrypt_proof(&liquidation_proof, &ctx.accounts.liquidator.key());
+    user_loan.liquidated = user_loan.collateral_amount == 0;
+
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.liquidator_token_account.to_account_info(),
+                to: ctx.accounts.pool_token_account.to_account_info(),
+                authority: ctx.accounts.liquidator.to_account_info(),
+            },
+        ),
+        repay_amount,
+    )?;
+
+    let seeds = &[b"pool".as_ref(), pool.mint.as_ref(), &[pool.bump]];
+    let signer = &[&seeds[..]];
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.pool_token_account.to_account_info(),
+                to: ctx.accounts.liquidator_token_account.to_account_info(),
+                authority: ctx.accounts.pool.to_account_info(),
+            },
+            signer,
+        ),
+        seize_amount,
+    )?;
+
+    let pool = &mut ctx.accounts.pool;
+    interest::repay_borrowed(pool, repay_amount)?;
+    pool.total_collateral = pool.total_collateral
+        .checked_sub(seize_amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    emit!(Liquidated {
+        pool: pool.key(),
+        owner: ctx.accounts.user_loan.owner,
+        liquidator: ctx.accounts.liquidator.key(),
+        repay_amount,
+        seize_amount,
+        fully_liquidated: ctx.accounts.user_loan.liquidated,
+        timestamp: now,
+    });
+
+    msg!(
+        "Liquidated {} debt for {} collateral (value {}) without front-running",
+        repay_amount,
+        seize_amount,
+        price.value(seize_amount)?
+    );
+    Ok(())
+}
+
+// Liquidated loans can still carry debt; that has to go through
+// write_off_bad_debt before the account can be closed.
+pub fn close_loan(ctx: Context<CloseLoan>) -> Result<()> {
+    let user_loan = &ctx.accounts.user_loan;
+    require!(
+        user_loan.borrowed == 0
+            && user_loan.collateral_amount == 0
+            && user_loan.active_auction == Pubkey::default(),
+        ErrorCode::LoanNotClosable
+    );
+
+    emit!(LoanClosed {
+        pool: ctx.accounts.pool.key(),
+        owner: user_loan.owner,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Loan for {} closed", user_loan.owner);
+    Ok(())
+}
+
+// Debt left on a loan whose collateral is gone is covered from protocol
+// reserves first; whatever remains is absorbed by suppliers.
+pub fn write_off_bad_debt(ctx: Context<WriteOffBadDebt>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    interest::accrue_interest(&mut ctx.accounts.pool, now)?;
+
+    let user_loan = &mut ctx.accounts.user_loan;
+    interest::sync_loan(user_loan, &ctx.accounts.pool)?;
+    require!(
+        user_loan.collateral_amount == 0
+            && user_loan.borrowed > 0
+            && user_loan.active_auction == Pubkey::default(),
+        ErrorCode::NoBadDebt
+    );
 
-    let encrypted_proof = encrypt_proof(&liquidation_proof, &ctx.accounts.liquidator.key());
-    user_loan.liquidation_proof = encrypted_proof;
+    // Debt the loan rounded up beyond the pool's booked total was never
+    // credited to suppliers, so only the booked part is a loss.
+    let pool = &mut ctx.accounts.pool;
+    let shortfall = user_loan.borrowed.min(pool.total_borrowed);
+    user_loan.borrowed = 0;
     user_loan.liquidated = true;
 
-    let pool = &ctx.accounts.pool;
-    let seeds = &[b"pool".as_ref(), &[pool.bump]];
-    let signer = &[&seeds[..]];
+    let covered_by_reserves = shortfall.min(pool.protocol_reserves);
+    let socialized = shortfall - covered_by_reserves;
+    pool.total_borrowed -= shortfall;
+    pool.protocol_reserves -= covered_by_reserves;
+    pool.total_deposits = pool.total_deposits
+        .checked_sub(socialized)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
 
-    token::transfer(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.pool_token_account.to_account_info(),
-                to: ctx.accounts.liquidator_token_account.to_account_info(),
-                authority: ctx.accounts.pool.to_account_info(),
-            },
-            signer,
-        ),
-        user_loan.collateral_amount,
-    )?;
+    emit!(BadDebtWrittenOff {
+        pool: pool.key(),
+        owner: user_loan.owner,
+        shortfall,
+        covered_by_reserves,
+        socialized,
+        timestamp: now,
+    });
 
-    msg!("Position liquidated without front-running");
+    msg!(
+        "Wrote off {} bad debt: {} from reserves, {} socialized",
+        shortfall,
+        covered_by_reserves,
+        socialized
+    );
     Ok(())
 }
 
@@ -144,6 +1230,132 @@ pub fn initialize_dark_pool(ctx: Context<InitializeDarkPool>, bump: u8) -> Resul
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_market(
+    ctx: Context<InitializeMarket>,
+    market_id: u64,
+    min_order_size: u64,
+    max_order_size: u64,
+    min_price: u64,
+    max_price: u64,
+    kyc_registry_root: [u8; 32],
+    bump: u8,
+) -> Result<()> {
+    require!(
+        min_order_size <= max_order_size && min_price <= max_price,
+        ErrorCode::InvalidMarketBounds
+    );
+
+    let market_config = &mut ctx.accounts.market_config;
+    market_config.authority = ctx.accounts.authority.key();
+    market_config.market_id = market_id;
+    market_config.min_order_size = min_order_size;
+    market_config.max_order_size = max_order_size;
+    market_config.min_price = min_price;
+    market_config.max_price = max_price;
+    market_config.kyc_registry_root = kyc_registry_root;
+    market_config.bump = bump;
+    market_config.base_mint = Pubkey::default();
+    market_config.quote_mint = Pubkey::default();
+    market_config.base_vault = Pubkey::default();
+    market_config.quote_vault = Pubkey::default();
+    market_config.base_lot_size = 0;
+    market_config.quote_lot_size = 0;
+    market_config.open_batch_id = 0;
+    market_config.sealed_batch_id = 0;
+
+    emit!(MarketInitialized {
+        market: market_config.key(),
+        market_id,
+        authority: market_config.authority,
+        min_order_size,
+        max_order_size,
+        min_price,
+        max_price,
+    });
+
+    msg!("Market {} initialized", market_id);
+    Ok(())
+}
+
+pub fn initialize_market_vaults(
+    ctx: Context<InitializeMarketVaults>,
+    base_lot_size: u64,
+    quote_lot_size: u64,
+    batch_bump: u8,
+) -> Result<()> {
+    require!(base_lot_size > 0 && quote_lot_size > 0, ErrorCode::InvalidMarketBounds);
+
+    // Batch ids are global and handed out by the order book
+    let order_book = &mut ctx.accounts.order_book;
+    let batch_id = order_book.next_batch_id;
+    order_book.next_batch_id = batch_id
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    open_batch(&mut ctx.accounts.batch, batch_id, ctx.accounts.market_config.key(), batch_bump)?;
+
+    let market_config = &mut ctx.accounts.market_config;
+    market_config.open_batch_id = batch_id;
+    market_config.base_lot_size = base_lot_size;
+    market_config.quote_lot_size = quote_lot_size;
+    market_config.base_mint = ctx.accounts.base_mint.key();
+    market_config.quote_mint = ctx.accounts.quote_mint.key();
+    market_config.base_vault = ctx.accounts.base_vault.key();
+    market_config.quote_vault = ctx.accounts.quote_vault.key();
+
+    emit!(MarketVaultsInitialized {
+        market: market_config.key(),
+        base_mint: market_config.base_mint,
+        quote_mint: market_config.quote_mint,
+        base_vault: market_config.base_vault,
+        quote_vault: market_config.quote_vault,
+        base_lot_size,
+        quote_lot_size,
+        open_batch_id: batch_id,
+    });
+
+    msg!(
+        "Market {} escrow vaults created for {}/{}",
+        market_config.market_id,
+        market_config.base_mint,
+        market_config.quote_mint
+    );
+    Ok(())
+}
+
+pub fn update_market_config(
+    ctx: Context<UpdateMarket>,
+    min_order_size: u64,
+    max_order_size: u64,
+    min_price: u64,
+    max_price: u64,
+    kyc_registry_root: [u8; 32],
+) -> Result<()> {
+    require!(
+        min_order_size <= max_order_size && min_price <= max_price,
+        ErrorCode::InvalidMarketBounds
+    );
+
+    let market_config = &mut ctx.accounts.market_config;
+    market_config.min_order_size = min_order_size;
+    market_config.max_order_size = max_order_size;
+    market_config.min_price = min_price;
+    market_config.max_price = max_price;
+    market_config.kyc_registry_root = kyc_registry_root;
+
+    emit!(MarketConfigUpdated {
+        market: market_config.key(),
+        min_order_size,
+        max_order_size,
+        min_price,
+        max_price,
+        kyc_registry_root,
+    });
+
+    msg!("Market {} config updated", market_config.market_id);
+    Ok(())
+}
+
 pub fn create_proof_account(ctx: Context<CreateProofAccount>, order_id: u64) -> Result<()> {
     let proof_account = &mut ctx.accounts.proof_account;
     proof_account.owner = ctx.accounts.user.key();
@@ -172,56 +1384,410 @@ pub fn store_order_proof(
     Ok(())
 }
 
+// Bids escrow quote tokens and asks escrow base tokens, padded to
+// lot_size * 2^escrow_tier. The order proof's solvency check must be made
+// against exactly the escrowed amount.
 pub fn submit_encrypted_order(
     ctx: Context<SubmitOrder>,
     order_id: u64,
     encrypted_data: Vec<u8>,
+    is_bid: bool,
+    escrow_tier: u8,
+    expires_at: i64,
 ) -> Result<()> {
     require!(!encrypted_data.is_empty(), ErrorCode::InvalidProof);
+    let now = Clock::get()?.unix_timestamp;
+    require!(expires_at > now, ErrorCode::InvalidExpiry);
+    require!(escrow_tier <= MAX_ESCROW_TIER, ErrorCode::InvalidEscrowTier);
     
     let proof_account = &ctx.accounts.proof_account;
-    require!(!proof_account.order_proof.is_empty(), ErrorCode::InvalidProof);
+    let market_config = &ctx.accounts.market_config;
+    
+    let (expected_vault, lot_size) = if is_bid {
+        (market_config.quote_vault, market_config.quote_lot_size)
+    } else {
+        (market_config.base_vault, market_config.base_lot_size)
+    };
+    require!(
+        expected_vault != Pubkey::default()
+            && ctx.accounts.escrow_vault.key() == expected_vault
+            && ctx.accounts.user_token_account.mint == ctx.accounts.escrow_vault.mint,
+        ErrorCode::InvalidEscrow
+    );
+    let escrow_amount = lot_size
+        .checked_mul(1u64 << escrow_tier)
+        .ok_or(ErrorCode::InvalidEscrowTier)?;
+    
+    let order_inputs = verifier::verify_proof(
+        &ctx.accounts.order_verifier,
+        &proof_account.order_proof,
+        &ctx.accounts.user.key(),
+    )?;
+    require!(
+        order_inputs.min_order_size == market_config.min_order_size
+            && order_inputs.max_order_size == market_config.max_order_size
+            && order_inputs.min_price == market_config.min_price
+            && order_inputs.max_price == market_config.max_price
+            && order_inputs.user_balance == escrow_amount,
+        ErrorCode::PublicInputMismatch
+    );
+    
+    let compliance_inputs = verifier::verify_proof(
+        &ctx.accounts.compliance_verifier,
+        &proof_account.compliance_proof,
+        &ctx.accounts.user.key(),
+    )?;
+    require!(
+        compliance_inputs.kyc_registry_root == market_config.kyc_registry_root,
+        ErrorCode::PublicInputMismatch
+    );
+    
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.user_token_account.to_account_info(),
+                to: ctx.accounts.escrow_vault.to_account_info(),
+                authority: ctx.accounts.user.to_account_info(),
+            },
+        ),
+        escrow_amount,
+    )?;
+    
+    join_batch(&mut ctx.accounts.batch, ctx.accounts.order.key())?;
     
     let order = &mut ctx.accounts.order;
     order.owner = ctx.accounts.user.key();
+    order.market = market_config.key();
     order.order_id = order_id;
     order.encrypted_data = encrypted_data;
     order.proof_account = ctx.accounts.proof_account.key();
-    order.timestamp = Clock::get()?.unix_timestamp;
+    order.timestamp = now;
     order.settled = false;
-    order.batch_id = 0;
+    order.batch_id = ctx.accounts.batch.batch_id;
+    order.is_bid = is_bid;
+    order.escrow_amount = escrow_amount;
+    order.expires_at = expires_at;
     
     let order_book = &mut ctx.accounts.order_book;
     order_book.total_orders = order_book.total_orders
         .checked_add(1)
         .ok_or(ProgramError::ArithmeticOverflow)?;
     
+    emit!(OrderSubmitted {
+        market: order.market,
+        order: order.key(),
+        owner: order.owner,
+        order_id,
+        timestamp: order.timestamp,
+    });
+
     msg!("Encrypted order {} submitted to dark pool", order_id);
     Ok(())
 }
 
-pub fn batch_match_orders(
-    ctx: Context<BatchMatch>,
-    batch_id: u64,
+// Orders can leave an open batch, or a settled one they were left behind in,
+// but not one the matcher is settling.
+fn join_batch(batch: &mut Batch, order: Pubkey) -> Result<()> {
+    require!(batch.status == BatchStatus::Open, ErrorCode::InvalidBatch);
+    require!(batch.orders.len() < MAX_BATCH_ORDERS, ErrorCode::BatchFull);
+    batch.orders.push(order);
+    Ok(())
+}
+
+fn leave_batch(batch: &mut Batch, order: &Pubkey) -> Result<()> {
+    require!(batch.status != BatchStatus::Sealed, ErrorCode::OrderLocked);
+    if batch.status == BatchStatus::Open {
+        batch.orders.retain(|member| member != order);
+    }
+    Ok(())
+}
+
+pub fn cancel_order(ctx: Context<CancelOrder>) -> Result<()> {
+    let order = &ctx.accounts.order;
+    require!(!order.settled, ErrorCode::OrderNotInBatch);
+    leave_batch(&mut ctx.accounts.batch, &order.key())?;
+
+    let market_config = &ctx.accounts.market_config;
+    let escrow_vault = if order.is_bid {
+        market_config.quote_vault
+    } else {
+        market_config.base_vault
+    };
+    require!(
+        ctx.accounts.escrow_vault.key() == escrow_vault
+            && ctx.accounts.user_token_account.mint == ctx.accounts.escrow_vault.mint,
+        ErrorCode::InvalidEscrow
+    );
+
+    market_transfer(
+        &ctx.accounts.token_program.to_account_info(),
+        market_config,
+        &ctx.accounts.escrow_vault.to_account_info(),
+        &ctx.accounts.user_token_account.to_account_info(),
+        order.escrow_amount,
+    )?;
+
+    emit!(OrderCancelled {
+        market: market_config.key(),
+        order: order.key(),
+        owner: order.owner,
+        order_id: order.order_id,
+        refund_amount: order.escrow_amount,
+        timestamp: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Order {} cancelled, {} tokens refunded", order.order_id, order.escrow_amount);
+    Ok(())
+}
+
+pub fn prune_expired_order(ctx: Context<PruneExpiredOrder>) -> Result<()> {
+    let order = &ctx.accounts.order;
+    require!(!order.settled, ErrorCode::OrderNotInBatch);
+    let now = Clock::get()?.unix_timestamp;
+    require!(
+        now >= order.expires_at,
+        ErrorCode::OrderNotExpired
+    );
+    leave_batch(&mut ctx.accounts.batch, &order.key())?;
+
+    let market_config = &ctx.accounts.market_config;
+    let escrow_vault = if order.is_bid {
+        market_config.quote_vault
+    } else {
+        market_config.base_vault
+    };
+    require!(
+        ctx.accounts.escrow_vault.key() == escrow_vault
+            && ctx.accounts.owner_token_account.mint == ctx.accounts.escrow_vault.mint,
+        ErrorCode::InvalidEscrow
+    );
+
+    market_transfer(
+        &ctx.accounts.token_program.to_account_info(),
+        market_config,
+        &ctx.accounts.escrow_vault.to_account_info(),
+        &ctx.accounts.owner_token_account.to_account_info(),
+        order.escrow_amount,
+    )?;
+
+    emit!(OrderPruned {
+        market: market_config.key(),
+        order: order.key(),
+        owner: order.owner,
+        crank: ctx.accounts.crank.key(),
+        order_id: order.order_id,
+        refund_amount: order.escrow_amount,
+        timestamp: now,
+    });
+
+    msg!(
+        "Expired order {} pruned by {}, {} tokens refunded",
+        order.order_id,
+        ctx.accounts.crank.key(),
+        order.escrow_amount
+    );
+    Ok(())
+}
+
+// Orders that found no room in the open batch when their batch settled are
+// still live; any crank can move them into the current open batch.
+pub fn requeue_order(ctx: Context<RequeueOrder>) -> Result<()> {
+    let order = &mut ctx.accounts.order;
+    require!(!order.settled, ErrorCode::OrderNotInBatch);
+    let now = Clock::get()?.unix_timestamp;
+    require!(order.expires_at > now, ErrorCode::OrderExpired);
+    
+    let open_batch = &mut ctx.accounts.open_batch;
+    join_batch(open_batch, order.key())?;
+    
+    let from_batch_id = order.batch_id;
+    order.batch_id = open_batch.batch_id;
+    
+    emit!(OrderRequeued {
+        market: order.market,
+        order: order.key(),
+        from_batch_id,
+        batch_id: order.batch_id,
+        timestamp: now,
+    });
+    
+    msg!(
+        "Order {} moved from batch {} to batch {}",
+        order.order_id,
+        from_batch_id,
+        order.batch_id
+    );
+    Ok(())
+}
+
+// Seals the market's open batch so its orders can no longer be cancelled;
+// new orders go to a freshly opened batch while the matcher settles this one.
+pub fn seal_batch(ctx: Context<SealBatch>, bump: u8) -> Result<()> {
+    require!(ctx.accounts.market_config.sealed_batch_id == 0, ErrorCode::BatchInProgress);
+
+    let batch = &mut ctx.accounts.batch;
+    require!(batch.status == BatchStatus::Open, ErrorCode::BatchInProgress);
+    let clock = Clock::get()?;
+    batch.status = BatchStatus::Sealed;
+    batch.close_slot = clock.slot;
+
+    ctx.accounts.market_config.sealed_batch_id = batch.batch_id;
+    let order_book = &mut ctx.accounts.order_book;
+    let next_batch_id = order_book.next_batch_id;
+    order_book.next_batch_id = next_batch_id
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    let market = ctx.accounts.market_config.key();
+    open_batch(&mut ctx.accounts.next_batch, next_batch_id, market, bump)?;
+    ctx.accounts.market_config.open_batch_id = next_batch_id;
+
+    emit!(BatchSealed {
+        market,
+        batch_id: ctx.accounts.batch.batch_id,
+        order_count: ctx.accounts.batch.orders.len() as u32,
+        next_batch_id,
+        timestamp: clock.unix_timestamp,
+    });
+
+    msg!(
+        "Batch {} sealed with {} orders, batch {} opened",
+        ctx.accounts.batch.batch_id,
+        ctx.accounts.batch.orders.len(),
+        next_batch_id
+    );
+    Ok(())
+}
+
+// Settles the sealed batch at a single clearing price. `fills[i]` is the base
+// amount filled for the batch's i-th order; orders with no fill roll over to
+// the market's open batch while it has room. Any left over once it is full
+// keep pointing at this batch until requeue_order moves them.
+pub fn batch_match_orders<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BatchMatch<'info>>,
     settlement_price: u64,
+    fills: Vec<u64>,
 ) -> Result<()> {
-    let order_book = &mut ctx.accounts.order_book;
-    
     require!(
-        ctx.accounts.authority.key() == order_book.authority,
+        ctx.accounts.authority.key() == ctx.accounts.order_book.authority,
         ErrorCode::Unauthorized
     );
+    require!(
+        ctx.accounts.batch.status == BatchStatus::Sealed,
+        ErrorCode::BatchNotSealed
+    );
+    let batch_id = ctx.accounts.batch.batch_id;
     
-    order_book.next_batch_id = batch_id
-        .checked_add(1)
-        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let market_config = &ctx.accounts.market_config;
+    require!(
+        settlement_price > 0
+            && settlement_price >= market_config.min_price
+            && settlement_price <= market_config.max_price,
+        ErrorCode::InvalidClearingPrice
+    );
+    require!(
+        fills.len() == ctx.accounts.batch.orders.len()
+            && ctx.remaining_accounts.len() == fills.len() * 3,
+        ErrorCode::InvalidBatch
+    );
+    
+    let open_batch_id = ctx.accounts.open_batch.batch_id;
+    let token_program = ctx.accounts.token_program.to_account_info();
+    let base_vault = ctx.accounts.base_vault.to_account_info();
+    let quote_vault = ctx.accounts.quote_vault.to_account_info();
+    let now = Clock::get()?.unix_timestamp;
+    let mut base_bought: u64 = 0;
+    let mut base_sold: u64 = 0;
+    
+    for (i, (accounts, &fill)) in ctx.remaining_accounts.chunks_exact(3).zip(fills.iter()).enumerate() {
+        require!(
+            accounts[0].key() == ctx.accounts.batch.orders[i],
+            ErrorCode::InvalidBatch
+        );
+        let mut order = Account::<EncryptedOrder>::try_from(&accounts[0])?;
+        require!(
+            order.market == market_config.key() && order.batch_id == batch_id && !order.settled,
+            ErrorCode::OrderNotInBatch
+        );
+        
+        // Expired orders are left in place for prune_expired_order
+        if order.expires_at <= now {
+            require!(fill == 0, ErrorCode::OrderExpired);
+            continue;
+        }
+        
+        if fill == 0 {
+            let open_batch = &mut ctx.accounts.open_batch;
+            if open_batch.orders.len() < MAX_BATCH_ORDERS {
+                join_batch(open_batch, order.key())?;
+                order.batch_id = open_batch_id;
+                order.exit(&crate::ID)?;
+            }
+            continue;
+        }
+        
+        let payout = Account::<TokenAccount>::try_from(&accounts[1])?;
+        let refund = Account::<TokenAccount>::try_from(&accounts[2])?;
+        let (payout_mint, escrow_mint) = if order.is_bid {
+            (market_config.base_mint, market_config.quote_mint)
+        } else {
+            (market_config.quote_mint, market_config.base_mint)
+        };
+        require!(
+            payout.owner == order.owner
+                && refund.owner == order.owner
+                && payout.mint == payout_mint
+                && refund.mint == escrow_mint,
+            ErrorCode::InvalidEscrow
+        );
+        
+        let (payout_amount, refund_amount) =
+            settle_fill(order.is_bid, fill, settlement_price, order.escrow_amount)?;
+        let (payout_vault, refund_vault) = if order.is_bid {
+            (&base_vault, &quote_vault)
+        } else {
+            (&quote_vault, &base_vault)
+        };
+        market_transfer(&token_program, market_config, payout_vault, &accounts[1], payout_amount)?;
+        market_transfer(&token_program, market_config, refund_vault, &accounts[2], refund_amount)?;
+        
+        let side_total = if order.is_bid { &mut base_bought } else { &mut base_sold };
+        *side_total = side_total
+            .checked_add(fill)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        
+        order.escrow_amount = 0;
+        order.settled = true;
+        order.exit(&crate::ID)?;
+    }
+    require!(base_bought == base_sold, ErrorCode::UnbalancedBatch);
+    
+    let batch = &mut ctx.accounts.batch;
+    batch.status = BatchStatus::Settled;
+    batch.clearing_price = settlement_price;
+    batch.fills = fills;
     
+    ctx.accounts.market_config.sealed_batch_id = 0;
+    
+    emit!(BatchMatched {
+        market: ctx.accounts.market_config.key(),
+        batch_id,
+        settlement_price,
+        base_volume: base_bought,
+        timestamp: now,
+    });
+
     msg!("Batch {} matched at price {}", batch_id, settlement_price);
     Ok(())
 }
 
 // Auction Instructions
 
+// Auctions move collateral out of the loan up front so it can't be withdrawn
+// or auctioned twice. Like liquidate, an auction sells at most the close
+// factor's worth of collateral plus the bonus, and the minimum bid must cover
+// the debt that collateral would repay.
 pub fn start_liquidation_auction(
     ctx: Context<StartAuction>,
     auction_id: u64,
@@ -230,22 +1796,80 @@ pub fn start_liquidation_auction(
     duration: u64,
     bump: u8,
 ) -> Result<()> {
-    let user_loan = &ctx.accounts.user_loan;
+    require!(collateral_amount > 0 && duration > 0, ErrorCode::InvalidAmount);
+    
+    let now = Clock::get()?.unix_timestamp;
+    let price = oracle::load_price(&ctx.accounts.oracle, now)?;
+    interest::accrue_interest(&mut ctx.accounts.pool, now)?;
+    
+    let user_loan = &mut ctx.accounts.user_loan;
     require!(!user_loan.liquidated, ErrorCode::PositionLiquidated);
+    require!(
+        user_loan.active_auction == Pubkey::default(),
+        ErrorCode::AuctionInProgress
+    );
+    interest::sync_loan(user_loan, &ctx.accounts.pool)?;
+    
+    let pool = &ctx.accounts.pool;
+    require!(
+        !within_threshold(
+            &price,
+            user_loan.collateral_amount,
+            user_loan.borrowed,
+            pool.liquidation_threshold_bps
+        )?,
+        ErrorCode::PositionHealthy
+    );
+    
+    let bonus_bps = (BPS_DENOMINATOR + pool.liquidation_bonus_bps) as u128;
+    let max_repay = user_loan.borrowed as u128 * pool.close_factor_bps as u128
+        / BPS_DENOMINATOR as u128;
+    let max_collateral = (max_repay.max(1) * bonus_bps / BPS_DENOMINATOR as u128)
+        .min(user_loan.collateral_amount as u128) as u64;
+    require!(
+        collateral_amount <= max_collateral,
+        ErrorCode::InsufficientCollateral
+    );
+    let bid_floor = (collateral_amount as u128 * BPS_DENOMINATOR as u128).div_ceil(bonus_bps) as u64;
+    require!(minimum_bid >= bid_floor, ErrorCode::BidBelowMinimum);
+    
+    user_loan.collateral_amount -= collateral_amount;
+    user_loan.active_auction = ctx.accounts.auction.key();
+    
+    let pool = &mut ctx.accounts.pool;
+    pool.total_collateral = pool.total_collateral
+        .checked_sub(collateral_amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
     
     let auction = &mut ctx.accounts.auction;
-    auction.position_owner = user_loan.owner;
+    auction.position_owner = ctx.accounts.user_loan.owner;
+    auction.pool = pool.key();
     auction.auction_id = auction_id;
     auction.collateral_amount = collateral_amount;
     auction.minimum_bid = minimum_bid;
-    auction.start_time = Clock::get()?.unix_timestamp;
+    auction.start_time = now;
     auction.duration = duration;
     auction.settled = false;
     auction.winning_bidder = Pubkey::default();
     auction.winning_bid = 0;
     auction.bump = bump;
     
-    msg!("Liquidation auction {} started", auction_id);
+    emit!(AuctionStarted {
+        auction: auction.key(),
+        pool: auction.pool,
+        position_owner: auction.position_owner,
+        auction_id,
+        collateral_amount,
+        minimum_bid,
+        end_time: now.saturating_add(duration as i64),
+    });
+    
+    msg!(
+        "Liquidation auction {} started for {} collateral (value {})",
+        auction_id,
+        collateral_amount,
+        price.value(collateral_amount)?
+    );
     Ok(())
 }
 
@@ -256,11 +1880,21 @@ pub fn submit_encrypted_bid(
     bid_proof: Vec<u8>,
 ) -> Result<()> {
     require!(!encrypted_bid.is_empty(), ErrorCode::InvalidProof);
-    require!(!bid_proof.is_empty(), ErrorCode::InvalidProof);
     
     let auction = &ctx.accounts.auction;
     require!(!auction.settled, ErrorCode::AuctionSettled);
     
+    let inputs = verifier::verify_proof(
+        &ctx.accounts.verifier_config,
+        &bid_proof,
+        &ctx.accounts.bidder.key(),
+    )?;
+    require!(
+        inputs.minimum_bid == auction.minimum_bid
+            && inputs.collateral_value == auction.collateral_amount,
+        ErrorCode::PublicInputMismatch
+    );
+    
     let current_time = Clock::get()?.unix_timestamp;
     let auction_end = auction.start_time
         .checked_add(auction.duration as i64)
@@ -274,26 +1908,59 @@ pub fn submit_encrypted_bid(
     bid.bid_proof = bid_proof;
     bid.timestamp = current_time;
     
+    emit!(BidSubmitted {
+        auction: ctx.accounts.auction.key(),
+        auction_id,
+        bidder: bid.bidder,
+        timestamp: current_time,
+    });
+    
     msg!("Encrypted bid submitted for auction {}", auction_id);
     Ok(())
 }
 
+fn auction_end(auction: &LiquidationAuction) -> Result<i64> {
+    Ok(auction.start_time
+        .checked_add(auction.duration as i64)
+        .ok_or(ProgramError::ArithmeticOverflow)?)
+}
+
+// The winner pays their bid into the vault against the loan's debt and takes
+// the auctioned collateral. Any bid above the debt is the borrower's and goes
+// back to their collateral.
 pub fn settle_auction(ctx: Context<SettleAuction>, winning_bid_amount: u64) -> Result<()> {
-    let auction = &mut ctx.accounts.auction;
+    let auction = &ctx.accounts.auction;
     require!(!auction.settled, ErrorCode::AuctionSettled);
+    require!(winning_bid_amount >= auction.minimum_bid, ErrorCode::BidBelowMinimum);
     
     let current_time = Clock::get()?.unix_timestamp;
-    let auction_end = auction.start_time
-        .checked_add(auction.duration as i64)
-        .ok_or(ProgramError::ArithmeticOverflow)?;
+    let auction_end = auction_end(auction)?;
     require!(current_time >= auction_end, ErrorCode::AuctionNotExpired);
+    require!(
+        current_time < auction_end.saturating_add(AUCTION_SETTLE_WINDOW_SECONDS),
+        ErrorCode::AuctionExpired
+    );
     
-    auction.winning_bidder = ctx.accounts.winner.key();
-    auction.winning_bid = winning_bid_amount;
-    auction.settled = true;
+    interest::accrue_interest(&mut ctx.accounts.pool, current_time)?;
+    let user_loan = &mut ctx.accounts.user_loan;
+    interest::sync_loan(user_loan, &ctx.accounts.pool)?;
+    let repay_amount = winning_bid_amount.min(user_loan.borrowed);
+    let surplus = winning_bid_amount - repay_amount;
+    
+    token::transfer(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.winner_token_account.to_account_info(),
+                to: ctx.accounts.pool_token_account.to_account_info(),
+                authority: ctx.accounts.winner.to_account_info(),
+            },
+        ),
+        winning_bid_amount,
+    )?;
     
     let pool = &ctx.accounts.pool;
-    let seeds = &[b"pool".as_ref(), &[pool.bump]];
+    let seeds = &[b"pool".as_ref(), pool.mint.as_ref(), &[pool.bump]];
     let signer = &[&seeds[..]];
     
     token::transfer(
@@ -306,9 +1973,238 @@ pub fn settle_auction(ctx: Context<SettleAuction>, winning_bid_amount: u64) -> R
             },
             signer,
         ),
-        auction.collateral_amount,
+        ctx.accounts.auction.collateral_amount,
     )?;
     
-    msg!("Auction {} settled with winning bid", auction.auction_id);
+    user_loan.borrowed -= repay_amount;
+    user_loan.collateral_amount = user_loan.collateral_amount
+        .checked_add(surplus)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    user_loan.active_auction = Pubkey::default();
+    user_loan.liquidated = user_loan.collateral_amount == 0;
+    
+    let pool = &mut ctx.accounts.pool;
+    interest::repay_borrowed(pool, repay_amount)?;
+    pool.total_collateral = pool.total_collateral
+        .checked_add(surplus)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    
+    let auction = &mut ctx.accounts.auction;
+    auction.winning_bidder = ctx.accounts.winner.key();
+    auction.winning_bid = winning_bid_amount;
+    auction.settled = true;
+    
+    emit!(AuctionSettled {
+        auction: auction.key(),
+        auction_id: auction.auction_id,
+        winner: auction.winning_bidder,
+        winning_bid: auction.winning_bid,
+        collateral_amount: auction.collateral_amount,
+        repay_amount,
+        timestamp: current_time,
+    });
+    
+    msg!(
+        "Auction {} settled, {} debt repaid from winning bid",
+        auction.auction_id,
+        repay_amount
+    );
+    Ok(())
+}
+
+// Auctions nobody settled in time hand their collateral back to the loan.
+pub fn cancel_auction(ctx: Context<CancelAuction>) -> Result<()> {
+    let auction = &mut ctx.accounts.auction;
+    require!(!auction.settled, ErrorCode::AuctionSettled);
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    require!(
+        current_time >= auction_end(auction)?.saturating_add(AUCTION_SETTLE_WINDOW_SECONDS),
+        ErrorCode::AuctionNotExpired
+    );
+    auction.settled = true;
+    
+    let user_loan = &mut ctx.accounts.user_loan;
+    user_loan.collateral_amount = user_loan.collateral_amount
+        .checked_add(auction.collateral_amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    user_loan.active_auction = Pubkey::default();
+    
+    let pool = &mut ctx.accounts.pool;
+    pool.total_collateral = pool.total_collateral
+        .checked_add(auction.collateral_amount)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    
+    emit!(AuctionCancelled {
+        auction: auction.key(),
+        auction_id: auction.auction_id,
+        position_owner: auction.position_owner,
+        collateral_amount: auction.collateral_amount,
+        timestamp: current_time,
+    });
+    
+    msg!("Auction {} cancelled, collateral returned", auction.auction_id);
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::sysvar::instructions::{
+        construct_instructions_data, BorrowedAccountMeta, BorrowedInstruction, ID as INSTRUCTIONS_ID,
+    };
+
+    // Instructions sysvar data for a transaction of (data, pool) calls to this program
+    fn transaction(calls: &[(Vec<u8>, Pubkey)]) -> Vec<u8> {
+        let instructions: Vec<BorrowedInstruction> = calls
+            .iter()
+            .map(|(data, pool)| BorrowedInstruction {
+                program_id: &crate::ID,
+                accounts: vec![BorrowedAccountMeta {
+                    pubkey: pool,
+                    is_signer: false,
+                    is_writable: true,
+                }],
+                data,
+            })
+            .collect();
+        construct_instructions_data(&instructions)
+    }
+
+    fn call(discriminator: &[u8], amount: u64) -> Vec<u8> {
+        [discriminator, &amount.to_le_bytes()].concat()
+    }
+
+    // Looks for the repay after a flash_borrow at index 0
+    fn scan_after_borrow(mut data: Vec<u8>, pool: &Pubkey, amount: u64) -> Result<()> {
+        let mut lamports = 0;
+        let account = AccountInfo::new(
+            &INSTRUCTIONS_ID,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &INSTRUCTIONS_ID,
+            false,
+            0,
+        );
+        find_flash_repay(&account, 1, pool, amount)
+    }
+
+    fn batch(status: BatchStatus, len: usize) -> Batch {
+        Batch {
+            batch_id: 1,
+            market: Pubkey::default(),
+            open_slot: 0,
+            close_slot: 0,
+            status,
+            clearing_price: 0,
+            orders: (0..len).map(|_| Pubkey::new_unique()).collect(),
+            fills: Vec::new(),
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn bid_cost_rounds_up() {
+        // 3 base at 0.333333 quote each costs 0.999999, rounded up to 1
+        assert_eq!(settle_fill(true, 3, 333_333, 10).unwrap(), (3, 9));
+        assert_eq!(settle_fill(true, 2, 1_500_000, 3).unwrap(), (2, 0));
+    }
+
+    #[test]
+    fn ask_proceeds_round_down() {
+        assert_eq!(settle_fill(false, 3, 333_333, 10).unwrap(), (0, 7));
+        assert_eq!(settle_fill(false, 2, 1_500_000, 2).unwrap(), (3, 0));
+    }
+
+    #[test]
+    fn matched_pair_never_pays_out_more_quote_than_escrowed() {
+        for (fill, price) in [(1, 1), (7, 142_857), (1_000, 999_999), (u32::MAX as u64, 1_234_567)] {
+            let cost = (fill as u128 * price as u128).div_ceil(PRICE_SCALE) as u64;
+            let (_, bid_refund) = settle_fill(true, fill, price, cost).unwrap();
+            let (proceeds, ask_refund) = settle_fill(false, fill, price, fill).unwrap();
+            assert_eq!((bid_refund, ask_refund), (0, 0));
+            assert!(proceeds <= cost);
+        }
+    }
+
+    #[test]
+    fn settle_fill_rejects_short_escrow() {
+        assert!(settle_fill(true, 2, 1_500_000, 2).is_err());
+        assert!(settle_fill(false, 3, 1_500_000, 2).is_err());
+    }
+
+    #[test]
+    fn join_batch_appends_while_open_with_room() {
+        let mut open = batch(BatchStatus::Open, MAX_BATCH_ORDERS - 1);
+        let order = Pubkey::new_unique();
+        join_batch(&mut open, order).unwrap();
+        assert_eq!(open.orders.last(), Some(&order));
+    }
+
+    #[test]
+    fn join_batch_rejects_full_or_closed_batches() {
+        let mut full = batch(BatchStatus::Open, MAX_BATCH_ORDERS);
+        assert!(join_batch(&mut full, Pubkey::new_unique()).is_err());
+        assert_eq!(full.orders.len(), MAX_BATCH_ORDERS);
+
+        let mut sealed = batch(BatchStatus::Sealed, 0);
+        assert!(join_batch(&mut sealed, Pubkey::new_unique()).is_err());
+        let mut settled = batch(BatchStatus::Settled, 0);
+        assert!(join_batch(&mut settled, Pubkey::new_unique()).is_err());
+    }
+
+    #[test]
+    fn leave_batch_is_locked_only_while_sealed() {
+        let mut open = batch(BatchStatus::Open, 2);
+        let order = open.orders[0];
+        leave_batch(&mut open, &order).unwrap();
+        assert!(!open.orders.contains(&order));
+
+        let mut sealed = batch(BatchStatus::Sealed, 1);
+        let order = sealed.orders[0];
+        assert!(leave_batch(&mut sealed, &order).is_err());
+
+        // Orders stranded by a settled batch can still leave it
+        let mut settled = batch(BatchStatus::Settled, 1);
+        let order = settled.orders[0];
+        leave_batch(&mut settled, &order).unwrap();
+        assert_eq!(settled.orders, vec![order]);
+    }
+
+    #[test]
+    fn flash_borrow_without_repay_is_rejected() {
+        let pool = Pubkey::new_unique();
+        let data = transaction(&[
+            (call(crate::instruction::FlashBorrow::DISCRIMINATOR, 1_000), pool),
+            (call(crate::instruction::Supply::DISCRIMINATOR, 1_000), pool),
+        ]);
+        assert_eq!(
+            scan_after_borrow(data, &pool, 1_000).unwrap_err(),
+            Error::from(ErrorCode::MissingFlashRepay)
+        );
+    }
+
+    #[test]
+    fn flash_repay_must_match_pool_and_amount() {
+        let pool = Pubkey::new_unique();
+        let borrow = (call(crate::instruction::FlashBorrow::DISCRIMINATOR, 1_000), pool);
+        let repay = |amount, pool| (call(crate::instruction::FlashRepay::DISCRIMINATOR, amount), pool);
+
+        let data = transaction(&[borrow.clone(), repay(1_000, pool)]);
+        assert!(scan_after_borrow(data, &pool, 1_000).is_ok());
+
+        let data = transaction(&[borrow.clone(), repay(1_000, Pubkey::new_unique())]);
+        assert_eq!(
+            scan_after_borrow(data, &pool, 1_000).unwrap_err(),
+            Error::from(ErrorCode::MissingFlashRepay)
+        );
+
+        let data = transaction(&[borrow, repay(999, pool)]);
+        assert_eq!(
+            scan_after_borrow(data, &pool, 1_000).unwrap_err(),
+            Error::from(ErrorCode::InvalidFlashLoan)
+        );
+    }
+}
@@ -0,0 +1,352 @@
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::state::{Pool, UserLoan};
+
+// Kinked utilization rate model: rates climb along slope1 up to the optimal
+// utilization and along the much steeper slope2 beyond it.
+pub fn borrow_rate_bps(pool: &Pool) -> Result<u64> {
+    let utilization = utilization_bps(pool)?;
+    let rate = if utilization <= pool.optimal_utilization_bps {
+        pool.slope1_bps
+            .checked_mul(utilization)
+            .and_then(|r| r.checked_div(pool.optimal_utilization_bps))
+            .and_then(|r| r.checked_add(pool.base_rate_bps))
+    } else {
+        pool.slope2_bps
+            .checked_mul(utilization - pool.optimal_utilization_bps)
+            .and_then(|r| r.checked_div(BPS_DENOMINATOR - pool.optimal_utilization_bps))
+            .and_then(|r| r.checked_add(pool.base_rate_bps))
+            .and_then(|r| r.checked_add(pool.slope1_bps))
+    };
+    Ok(rate.ok_or(ProgramError::ArithmeticOverflow)?)
+}
+
+pub fn supply_rate_bps(pool: &Pool) -> Result<u64> {
+    let rate = (borrow_rate_bps(pool)? as u128)
+        .checked_mul(utilization_bps(pool)? as u128)
+        .and_then(|r| r.checked_mul((BPS_DENOMINATOR - pool.reserve_factor_bps) as u128))
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    to_u64(rate / (BPS_DENOMINATOR as u128 * BPS_DENOMINATOR as u128))
+}
+
+pub fn utilization_bps(pool: &Pool) -> Result<u64> {
+    if pool.total_deposits == 0 {
+        return Ok(0);
+    }
+    let utilization = (pool.total_borrowed as u128)
+        .checked_mul(BPS_DENOMINATOR as u128)
+        .ok_or(ProgramError::ArithmeticOverflow)?
+        / pool.total_deposits as u128;
+    Ok(utilization.min(BPS_DENOMINATOR as u128) as u64)
+}
+
+// Grows the borrow index by the interest accrued since the last update and
+// credits it to suppliers, less the reserve factor kept by the protocol.
+pub fn accrue_interest(pool: &mut Pool, now: i64) -> Result<()> {
+    let elapsed = now.saturating_sub(pool.last_accrual_timestamp);
+    if elapsed <= 0 {
+        return Ok(());
+    }
+
+    let factor = (borrow_rate_bps(pool)? as u128)
+        .checked_mul(INDEX_SCALE)
+        .and_then(|f| f.checked_mul(elapsed as u128))
+        .ok_or(ProgramError::ArithmeticOverflow)?
+        / (BPS_DENOMINATOR as u128 * SECONDS_PER_YEAR as u128);
+
+    pool.borrow_index = pool.borrow_index
+        .checked_add(scale(pool.borrow_index, factor)?)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    let interest = to_u64(scale(pool.total_borrowed as u128, factor)?)?;
+    pool.total_borrowed = pool.total_borrowed
+        .checked_add(interest)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+
+    credit_revenue(pool, interest)?;
+
+    pool.last_accrual_timestamp = now;
+    Ok(())
+}
+
+// Brings a loan's debt up to the pool's current borrow index, rounding up.
+pub fn sync_loan(user_loan: &mut UserLoan, pool: &Pool) -> Result<()> {
+    let snapshot = user_loan.borrow_index_snapshot;
+    if user_loan.borrowed > 0 && snapshot > 0 {
+        let numerator = (user_loan.borrowed as u128)
+            .checked_mul(pool.borrow_index)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        user_loan.borrowed = to_u64(numerator.div_ceil(snapshot))?;
+    }
+    user_loan.borrow_index_snapshot = pool.borrow_index;
+    Ok(())
+}
+
+// Shares minted for supplying `amount` at the current exchange rate.
+pub fn shares_for_amount(pool: &Pool, amount: u64) -> Result<u64> {
+    if pool.total_shares == 0 || pool.total_deposits == 0 {
+        return Ok(amount);
+    }
+    to_u64(
+        (amount as u128)
+            .checked_mul(pool.total_shares as u128)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            / pool.total_deposits as u128,
+    )
+}
+
+// Underlying owed for redeeming `shares` at the current exchange rate.
+pub fn amount_for_shares(pool: &Pool, shares: u64) -> Result<u64> {
+    if pool.total_shares == 0 {
+        return Ok(0);
+    }
+    to_u64(
+        (shares as u128)
+            .checked_mul(pool.total_deposits as u128)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            / pool.total_shares as u128,
+    )
+}
+
+// Removes repaid debt from the pool. Loans round their debt up from the index
+// while the pool total rounds down, so a repayment can exceed the booked debt
+// by a few units; that excess is interest the pool hadn't accrued yet.
+pub fn repay_borrowed(pool: &mut Pool, amount: u64) -> Result<()> {
+    let booked = amount.min(pool.total_borrowed);
+    pool.total_borrowed -= booked;
+    credit_revenue(pool, amount - booked)
+}
+
+// Flash loan fees round up so a loan never goes free.
+pub fn flash_loan_fee(pool: &Pool, amount: u64) -> u64 {
+    ((amount as u128) * pool.flash_loan_fee_bps as u128).div_ceil(BPS_DENOMINATOR as u128) as u64
+}
+
+// Returns a repaid flash loan to the pool. The whole fee goes to suppliers,
+// raising the share exchange rate.
+pub fn close_flash_loan(pool: &mut Pool, fee: u64) -> Result<()> {
+    pool.flash_loan_amount = 0;
+    pool.total_deposits = pool.total_deposits
+        .checked_add(fee)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    Ok(())
+}
+
+// Splits borrower-paid revenue between protocol reserves and suppliers.
+pub fn credit_revenue(pool: &mut Pool, amount: u64) -> Result<()> {
+    let reserve = to_u64(
+        (amount as u128)
+            .checked_mul(pool.reserve_factor_bps as u128)
+            .ok_or(ProgramError::ArithmeticOverflow)?
+            / BPS_DENOMINATOR as u128,
+    )?;
+    pool.protocol_reserves = pool.protocol_reserves
+        .checked_add(reserve)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    pool.total_deposits = pool.total_deposits
+        .checked_add(amount - reserve)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    Ok(())
+}
+
+// Liquidity suppliers can draw on; protocol reserves are not lendable and
+// an outstanding flash loan is out of the vault until repaid.
+pub fn available_liquidity(pool: &Pool) -> u64 {
+    pool.total_deposits
+        .saturating_sub(pool.total_borrowed)
+        .saturating_sub(pool.flash_loan_amount)
+}
+
+// Tokens the pool holds or has lent out on behalf of suppliers and borrowers,
+// which is what the deposit cap bounds.
+pub fn deposit_exposure(pool: &Pool) -> u64 {
+    pool.total_deposits.saturating_add(pool.total_collateral)
+}
+
+// Idle supplier and reserve funds held by the vault, excluding collateral.
+pub fn pool_cash(pool: &Pool) -> u64 {
+    pool.total_deposits
+        .saturating_add(pool.protocol_reserves)
+        .saturating_sub(pool.total_borrowed)
+        .saturating_sub(pool.flash_loan_amount)
+}
+
+fn scale(value: u128, factor: u128) -> Result<u128> {
+    Ok(value
+        .checked_mul(factor)
+        .ok_or(ProgramError::ArithmeticOverflow)?
+        / INDEX_SCALE)
+}
+
+fn to_u64(value: u128) -> Result<u64> {
+    Ok(u64::try_from(value).map_err(|_| ProgramError::ArithmeticOverflow)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pool(total_deposits: u64, total_borrowed: u64) -> Pool {
+        Pool {
+            authority: Pubkey::default(),
+            bump: 0,
+            mint: Pubkey::default(),
+            vault: Pubkey::default(),
+            total_deposits,
+            total_borrowed,
+            kyc_registry_root: [0; 32],
+            base_rate_bps: 200,
+            slope1_bps: 400,
+            optimal_utilization_bps: 8_000,
+            slope2_bps: 6_000,
+            borrow_index: INDEX_SCALE,
+            last_accrual_timestamp: 0,
+            oracle: Pubkey::default(),
+            max_ltv_bps: 7_500,
+            liquidation_threshold_bps: 8_000,
+            close_factor_bps: 5_000,
+            liquidation_bonus_bps: 500,
+            share_mint: Pubkey::default(),
+            total_shares: 0,
+            total_collateral: 0,
+            reserve_factor_bps: 1_000,
+            origination_fee_bps: 0,
+            protocol_reserves: 0,
+            treasury: Pubkey::default(),
+            flash_loan_fee_bps: 0,
+            flash_loan_amount: 0,
+            deposit_cap: 0,
+            borrow_cap: 0,
+        }
+    }
+
+    fn loan(borrowed: u64, borrow_index_snapshot: u128) -> UserLoan {
+        UserLoan {
+            owner: Pubkey::default(),
+            pool: Pubkey::default(),
+            collateral_amount: 0,
+            collateral_encrypted: Vec::new(),
+            borrowed,
+            ltv_proof: Vec::new(),
+            liquidation_proof: Vec::new(),
+            liquidated: false,
+            deposit_timestamp: 0,
+            borrow_index_snapshot,
+            active_auction: Pubkey::default(),
+        }
+    }
+
+    #[test]
+    fn borrow_rate_follows_slope1_up_to_the_kink() {
+        assert_eq!(borrow_rate_bps(&pool(10_000, 0)).unwrap(), 200);
+        assert_eq!(borrow_rate_bps(&pool(10_000, 4_000)).unwrap(), 400);
+        assert_eq!(borrow_rate_bps(&pool(10_000, 8_000)).unwrap(), 600);
+    }
+
+    #[test]
+    fn borrow_rate_follows_slope2_past_the_kink() {
+        assert_eq!(borrow_rate_bps(&pool(10_000, 9_000)).unwrap(), 3_600);
+        assert_eq!(borrow_rate_bps(&pool(10_000, 10_000)).unwrap(), 6_600);
+        // Utilization is capped at 100%
+        assert_eq!(borrow_rate_bps(&pool(10_000, 20_000)).unwrap(), 6_600);
+    }
+
+    #[test]
+    fn accrue_interest_grows_index_and_splits_revenue() {
+        let mut pool = pool(10_000, 4_000);
+        accrue_interest(&mut pool, SECONDS_PER_YEAR as i64).unwrap();
+
+        assert_eq!(pool.borrow_index, INDEX_SCALE * 104 / 100);
+        assert_eq!(pool.total_borrowed, 4_160);
+        assert_eq!(pool.protocol_reserves, 16);
+        assert_eq!(pool.total_deposits, 10_144);
+        assert_eq!(pool.last_accrual_timestamp, SECONDS_PER_YEAR as i64);
+    }
+
+    #[test]
+    fn accrue_interest_ignores_non_positive_elapsed() {
+        let mut pool = pool(10_000, 4_000);
+        pool.last_accrual_timestamp = 100;
+        accrue_interest(&mut pool, 100).unwrap();
+        accrue_interest(&mut pool, 50).unwrap();
+
+        assert_eq!(pool.borrow_index, INDEX_SCALE);
+        assert_eq!(pool.total_borrowed, 4_000);
+        assert_eq!(pool.last_accrual_timestamp, 100);
+    }
+
+    #[test]
+    fn sync_loan_rounds_debt_up() {
+        let mut pool = pool(10_000, 4_000);
+        accrue_interest(&mut pool, 1).unwrap();
+        let mut loan = loan(1_000, INDEX_SCALE);
+        sync_loan(&mut loan, &pool).unwrap();
+
+        assert_eq!(loan.borrowed, 1_001);
+        assert_eq!(loan.borrow_index_snapshot, pool.borrow_index);
+    }
+
+    #[test]
+    fn sync_loan_only_snapshots_without_debt() {
+        let pool = pool(10_000, 0);
+        let mut loan = loan(0, 0);
+        sync_loan(&mut loan, &pool).unwrap();
+
+        assert_eq!(loan.borrowed, 0);
+        assert_eq!(loan.borrow_index_snapshot, INDEX_SCALE);
+    }
+
+    #[test]
+    fn full_repayment_clears_pool_debt_despite_rounding() {
+        let mut pool = pool(1_000_000, 1_000);
+        let mut loan = loan(1_000, INDEX_SCALE);
+        for now in 1..=3 {
+            accrue_interest(&mut pool, now).unwrap();
+        }
+        sync_loan(&mut loan, &pool).unwrap();
+
+        // The loan rounds up while the pool total rounds down
+        assert_eq!(loan.borrowed, 1_001);
+        assert_eq!(pool.total_borrowed, 1_000);
+
+        repay_borrowed(&mut pool, loan.borrowed).unwrap();
+        assert_eq!(pool.total_borrowed, 0);
+        assert_eq!(pool.total_deposits, 1_000_001);
+    }
+
+    #[test]
+    fn shares_are_one_to_one_for_an_empty_pool() {
+        let pool = pool(0, 0);
+        assert_eq!(shares_for_amount(&pool, 500).unwrap(), 500);
+        assert_eq!(amount_for_shares(&pool, 500).unwrap(), 0);
+    }
+
+    #[test]
+    fn shares_convert_at_the_exchange_rate_rounding_down() {
+        let mut pool = pool(1_100, 0);
+        pool.total_shares = 1_000;
+
+        assert_eq!(shares_for_amount(&pool, 110).unwrap(), 100);
+        assert_eq!(amount_for_shares(&pool, 100).unwrap(), 110);
+        assert_eq!(shares_for_amount(&pool, 1).unwrap(), 0);
+        assert_eq!(amount_for_shares(&pool, 1).unwrap(), 1);
+    }
+
+    #[test]
+    fn flash_repay_credits_the_fee_to_suppliers() {
+        let mut pool = pool(10_000, 0);
+        pool.flash_loan_fee_bps = 9;
+        pool.flash_loan_amount = 1_000;
+        assert_eq!(available_liquidity(&pool), 9_000);
+
+        // 0.9 tokens of fee rounds up to 1
+        let fee = flash_loan_fee(&pool, 1_000);
+        assert_eq!(fee, 1);
+        close_flash_loan(&mut pool, fee).unwrap();
+
+        assert_eq!(pool.flash_loan_amount, 0);
+        assert_eq!(pool.total_deposits, 10_001);
+        assert_eq!(pool.protocol_reserves, 0);
+        assert_eq!(available_liquidity(&pool), 10_001);
+    }
+}
@@ -5,11 +5,19 @@ mod errors;
 mod state;
 mod instructions;
 mod contexts;
+mod verifier;
+mod interest;
+mod oracle;
+mod events;
 
 pub use constants::*;
 //pub use errors::*;
 pub use state::*;
 pub use contexts::*;
+pub use verifier::*;
+pub use interest::*;
+pub use oracle::*;
+pub use events::*;
 
 declare_id!("GbEuxhGpP1iy7YouyvfrDPEKk6pHZhZ8oT5oxLMvbGQ3");
 
@@ -17,26 +25,227 @@ declare_id!("GbEuxhGpP1iy7YouyvfrDPEKk6pHZhZ8oT5oxLMvbGQ3");
 pub mod obsidian_protocol {
     use super::*;
 
+    pub fn initialize_program_config(
+        ctx: Context<InitializeProgramConfig>,
+        bump: u8,
+    ) -> Result<()> {
+        instructions::initialize_program_config(ctx, bump)
+    }
+
+    pub fn set_program_admin(ctx: Context<UpdateProgramConfig>, new_admin: Pubkey) -> Result<()> {
+        instructions::set_program_admin(ctx, new_admin)
+    }
+
     pub fn initialize_pool(ctx: Context<InitializePool>, bump: u8) -> Result<()> {
         instructions::initialize_pool(ctx, bump)
     }
 
+    pub fn set_kyc_registry_root(
+        ctx: Context<UpdatePool>,
+        kyc_registry_root: [u8; 32],
+    ) -> Result<()> {
+        instructions::set_kyc_registry_root(ctx, kyc_registry_root)
+    }
+
+    pub fn set_interest_rate_model(
+        ctx: Context<UpdatePool>,
+        base_rate_bps: u64,
+        slope1_bps: u64,
+        optimal_utilization_bps: u64,
+        slope2_bps: u64,
+    ) -> Result<()> {
+        instructions::set_interest_rate_model(
+            ctx,
+            base_rate_bps,
+            slope1_bps,
+            optimal_utilization_bps,
+            slope2_bps,
+        )
+    }
+
+    pub fn set_risk_parameters(
+        ctx: Context<UpdatePool>,
+        max_ltv_bps: u64,
+        liquidation_threshold_bps: u64,
+        close_factor_bps: u64,
+        liquidation_bonus_bps: u64,
+    ) -> Result<()> {
+        instructions::set_risk_parameters(
+            ctx,
+            max_ltv_bps,
+            liquidation_threshold_bps,
+            close_factor_bps,
+            liquidation_bonus_bps,
+        )
+    }
+
+    pub fn set_pool_oracle(ctx: Context<UpdatePool>, oracle: Pubkey) -> Result<()> {
+        instructions::set_pool_oracle(ctx, oracle)
+    }
+
+    pub fn set_reserve_config(
+        ctx: Context<UpdatePool>,
+        reserve_factor_bps: u64,
+        origination_fee_bps: u64,
+        treasury: Pubkey,
+    ) -> Result<()> {
+        instructions::set_reserve_config(ctx, reserve_factor_bps, origination_fee_bps, treasury)
+    }
+
+    pub fn set_pool_caps(ctx: Context<UpdatePool>, deposit_cap: u64, borrow_cap: u64) -> Result<()> {
+        instructions::set_pool_caps(ctx, deposit_cap, borrow_cap)
+    }
+
+    pub fn set_flash_loan_fee(ctx: Context<UpdatePool>, flash_loan_fee_bps: u64) -> Result<()> {
+        instructions::set_flash_loan_fee(ctx, flash_loan_fee_bps)
+    }
+
+    pub fn collect_reserves(ctx: Context<CollectReserves>) -> Result<()> {
+        instructions::collect_reserves(ctx)
+    }
+
+    pub fn initialize_price_feed(
+        ctx: Context<InitializePriceFeed>,
+        expo: i32,
+        bump: u8,
+    ) -> Result<()> {
+        instructions::initialize_price_feed(ctx, expo, bump)
+    }
+
+    pub fn update_price_feed(ctx: Context<UpdatePriceFeed>, price: i64, conf: u64) -> Result<()> {
+        instructions::update_price_feed(ctx, price, conf)
+    }
+
+    pub fn initialize_verifier(
+        ctx: Context<InitializeVerifier>,
+        circuit_type: u64,
+        bump: u8,
+    ) -> Result<()> {
+        instructions::initialize_verifier(ctx, circuit_type, bump)
+    }
+
+    pub fn store_verifying_key(ctx: Context<UpdateVerifier>, chunk: Vec<u8>) -> Result<()> {
+        instructions::store_verifying_key(ctx, chunk)
+    }
+
+    pub fn activate_verifying_key(ctx: Context<UpdateVerifier>) -> Result<()> {
+        instructions::activate_verifying_key(ctx)
+    }
+
     pub fn deposit(ctx: Context<Deposit>, amount: u64, proof_data: Vec<u8>) -> Result<()> {
         instructions::deposit(ctx, amount, proof_data)
     }
 
+    pub fn supply(ctx: Context<Supply>, amount: u64) -> Result<()> {
+        instructions::supply(ctx, amount)
+    }
+
+    pub fn redeem(ctx: Context<Redeem>, shares: u64) -> Result<()> {
+        instructions::redeem(ctx, shares)
+    }
+
+    pub fn set_borrow_allowance(
+        ctx: Context<SetBorrowAllowance>,
+        amount: u64,
+        bump: u8,
+    ) -> Result<()> {
+        instructions::set_borrow_allowance(ctx, amount, bump)
+    }
+
+    pub fn flash_borrow(ctx: Context<FlashBorrow>, amount: u64) -> Result<()> {
+        instructions::flash_borrow(ctx, amount)
+    }
+
+    pub fn flash_repay(ctx: Context<FlashRepay>, amount: u64) -> Result<()> {
+        instructions::flash_repay(ctx, amount)
+    }
+
     pub fn borrow(ctx: Context<Borrow>, amount: u64, ltv_proof: Vec<u8>) -> Result<()> {
         instructions::borrow(ctx, amount, ltv_proof)
     }
 
-    pub fn liquidate(ctx: Context<Liquidate>, liquidation_proof: Vec<u8>) -> Result<()> {
-        instructions::liquidate(ctx, liquidation_proof)
+    pub fn withdraw_collateral(
+        ctx: Context<WithdrawCollateral>,
+        amount: u64,
+        ltv_proof: Vec<u8>,
+    ) -> Result<()> {
+        instructions::withdraw_collateral(ctx, amount, ltv_proof)
+    }
+
+    pub fn repay(ctx: Context<Repay>, amount: u64) -> Result<()> {
+        instructions::repay(ctx, amount)
+    }
+
+    pub fn liquidate(
+        ctx: Context<Liquidate>,
+        repay_amount: u64,
+        liquidation_proof: Vec<u8>,
+    ) -> Result<()> {
+        instructions::liquidate(ctx, repay_amount, liquidation_proof)
+    }
+
+    pub fn close_loan(ctx: Context<CloseLoan>) -> Result<()> {
+        instructions::close_loan(ctx)
+    }
+
+    pub fn write_off_bad_debt(ctx: Context<WriteOffBadDebt>) -> Result<()> {
+        instructions::write_off_bad_debt(ctx)
     }
 
     pub fn initialize_dark_pool(ctx: Context<InitializeDarkPool>, bump: u8) -> Result<()> {
         instructions::initialize_dark_pool(ctx, bump)
     }
 
+    #[allow(clippy::too_many_arguments)]
+    pub fn initialize_market(
+        ctx: Context<InitializeMarket>,
+        market_id: u64,
+        min_order_size: u64,
+        max_order_size: u64,
+        min_price: u64,
+        max_price: u64,
+        kyc_registry_root: [u8; 32],
+        bump: u8,
+    ) -> Result<()> {
+        instructions::initialize_market(
+            ctx,
+            market_id,
+            min_order_size,
+            max_order_size,
+            min_price,
+            max_price,
+            kyc_registry_root,
+            bump,
+        )
+    }
+
+    pub fn update_market_config(
+        ctx: Context<UpdateMarket>,
+        min_order_size: u64,
+        max_order_size: u64,
+        min_price: u64,
+        max_price: u64,
+        kyc_registry_root: [u8; 32],
+    ) -> Result<()> {
+        instructions::update_market_config(
+            ctx,
+            min_order_size,
+            max_order_size,
+            min_price,
+            max_price,
+            kyc_registry_root,
+        )
+    }
+
+    pub fn initialize_market_vaults(
+        ctx: Context<InitializeMarketVaults>,
+        base_lot_size: u64,
+        quote_lot_size: u64,
+        batch_bump: u8,
+    ) -> Result<()> {
+        instructions::initialize_market_vaults(ctx, base_lot_size, quote_lot_size, batch_bump)
+    }
+
     pub fn create_proof_account(ctx: Context<CreateProofAccount>, order_id: u64) -> Result<()> {
         instructions::create_proof_account(ctx, order_id)
     }
@@ -53,16 +262,42 @@ pub mod obsidian_protocol {
         ctx: Context<SubmitOrder>,
     order_id: u64,
     encrypted_data: Vec<u8>,
+        is_bid: bool,
+        escrow_tier: u8,
+        expires_at: i64,
     ) -> Result<()> {
-        instructions::submit_encrypted_order(ctx, order_id, encrypted_data)
+        instructions::submit_encrypted_order(
+            ctx,
+            order_id,
+            encrypted_data,
+            is_bid,
+            escrow_tier,
+            expires_at,
+        )
+    }
+
+    pub fn cancel_order(ctx: Context<CancelOrder>) -> Result<()> {
+        instructions::cancel_order(ctx)
     }
 
-    pub fn batch_match_orders(
-    ctx: Context<BatchMatch>,
-        batch_id: u64,
+    pub fn prune_expired_order(ctx: Context<PruneExpiredOrder>) -> Result<()> {
+        instructions::prune_expired_order(ctx)
+    }
+
+    pub fn requeue_order(ctx: Context<RequeueOrder>) -> Result<()> {
+        instructions::requeue_order(ctx)
+    }
+
+    pub fn seal_batch(ctx: Context<SealBatch>, bump: u8) -> Result<()> {
+        instructions::seal_batch(ctx, bump)
+    }
+
+    pub fn batch_match_orders<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BatchMatch<'info>>,
         settlement_price: u64,
+        fills: Vec<u64>,
     ) -> Result<()> {
-        instructions::batch_match_orders(ctx, batch_id, settlement_price)
+        instructions::batch_match_orders(ctx, settlement_price, fills)
     }
 
     pub fn start_liquidation_auction(
@@ -94,4 +329,8 @@ pub mod obsidian_protocol {
     pub fn settle_auction(ctx: Context<SettleAuction>, winning_bid_amount: u64) -> Result<()> {
         instructions::settle_auction(ctx, winning_bid_amount)
     }
+
+    pub fn cancel_auction(ctx: Context<CancelAuction>) -> Result<()> {
+        instructions::cancel_auction(ctx)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,163 @@
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::errors::ErrorCode;
+use crate::state::PriceFeed;
+
+// Pyth v2 price account layout
+const PYTH_MAGIC: u32 = 0xa1b2c3d4;
+const PYTH_VERSION: u32 = 2;
+const PYTH_PRICE_ACCOUNT: u32 = 3;
+const PYTH_STATUS_TRADING: u32 = 1;
+const PYTH_EXPO_OFFSET: usize = 20;
+const PYTH_TIMESTAMP_OFFSET: usize = 96;
+const PYTH_AGG_PRICE_OFFSET: usize = 208;
+const PYTH_AGG_CONF_OFFSET: usize = 216;
+const PYTH_AGG_STATUS_OFFSET: usize = 224;
+const PYTH_MIN_LEN: usize = 240;
+
+pub struct OraclePrice {
+    pub price: u64,
+    pub expo: i32,
+    pub conf: u64,
+    pub publish_time: i64,
+}
+
+impl OraclePrice {
+    // Value of `amount` base units, expressed with VALUE_DECIMALS of precision.
+    pub fn value(&self, amount: u64) -> Result<u128> {
+        let raw = (amount as u128)
+            .checked_mul(self.price as u128)
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        let shift = self.expo + VALUE_DECIMALS;
+        let scale = 10u128
+            .checked_pow(shift.unsigned_abs())
+            .ok_or(ProgramError::ArithmeticOverflow)?;
+        if shift >= 0 {
+            Ok(raw.checked_mul(scale).ok_or(ProgramError::ArithmeticOverflow)?)
+        } else {
+            Ok(raw / scale)
+        }
+    }
+}
+
+// Reads either a program-owned PriceFeed or a Pyth price account and
+// rejects prices that are stale or too uncertain to value collateral with.
+pub fn load_price(oracle: &AccountInfo, now: i64) -> Result<OraclePrice> {
+    let data = oracle.try_borrow_data()?;
+    let (price, expo, conf, publish_time) = if *oracle.owner == crate::ID {
+        let feed = PriceFeed::try_deserialize(&mut &data[..])?;
+        (feed.price, feed.expo, feed.conf, feed.publish_time)
+    } else {
+        parse_pyth(&data)?
+    };
+
+    require!(price > 0, ErrorCode::InvalidOracle);
+    require!(
+        now.saturating_sub(publish_time) <= MAX_PRICE_AGE_SECONDS,
+        ErrorCode::StalePrice
+    );
+    require!(
+        (conf as u128) * (BPS_DENOMINATOR as u128) <= (price as u128) * (MAX_CONFIDENCE_BPS as u128),
+        ErrorCode::PriceConfidenceTooWide
+    );
+
+    Ok(OraclePrice {
+        price: price as u64,
+        expo,
+        conf,
+        publish_time,
+    })
+}
+
+fn parse_pyth(data: &[u8]) -> Result<(i64, i32, u64, i64)> {
+    require!(data.len() >= PYTH_MIN_LEN, ErrorCode::InvalidOracle);
+    require!(
+        read_u32(data, 0) == PYTH_MAGIC
+            && read_u32(data, 4) == PYTH_VERSION
+            && read_u32(data, 8) == PYTH_PRICE_ACCOUNT,
+        ErrorCode::InvalidOracle
+    );
+    require!(
+        read_u32(data, PYTH_AGG_STATUS_OFFSET) == PYTH_STATUS_TRADING,
+        ErrorCode::InvalidOracle
+    );
+
+    Ok((
+        read_u64(data, PYTH_AGG_PRICE_OFFSET) as i64,
+        read_u32(data, PYTH_EXPO_OFFSET) as i32,
+        read_u64(data, PYTH_AGG_CONF_OFFSET),
+        read_u64(data, PYTH_TIMESTAMP_OFFSET) as i64,
+    ))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(price: u64, expo: i32) -> OraclePrice {
+        OraclePrice {
+            price,
+            expo,
+            conf: 0,
+            publish_time: 0,
+        }
+    }
+
+    fn pyth_account(price: i64, expo: i32, status: u32) -> Vec<u8> {
+        let mut data = vec![0u8; PYTH_MIN_LEN];
+        data[0..4].copy_from_slice(&PYTH_MAGIC.to_le_bytes());
+        data[4..8].copy_from_slice(&PYTH_VERSION.to_le_bytes());
+        data[8..12].copy_from_slice(&PYTH_PRICE_ACCOUNT.to_le_bytes());
+        data[PYTH_EXPO_OFFSET..PYTH_EXPO_OFFSET + 4].copy_from_slice(&expo.to_le_bytes());
+        data[PYTH_TIMESTAMP_OFFSET..PYTH_TIMESTAMP_OFFSET + 8].copy_from_slice(&1_700i64.to_le_bytes());
+        data[PYTH_AGG_PRICE_OFFSET..PYTH_AGG_PRICE_OFFSET + 8].copy_from_slice(&price.to_le_bytes());
+        data[PYTH_AGG_CONF_OFFSET..PYTH_AGG_CONF_OFFSET + 8].copy_from_slice(&25u64.to_le_bytes());
+        data[PYTH_AGG_STATUS_OFFSET..PYTH_AGG_STATUS_OFFSET + 4].copy_from_slice(&status.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn value_shifts_down_for_small_exponents() {
+        // 1.5 at expo -8 is two decimals finer than VALUE_DECIMALS
+        assert_eq!(price(150_000_000, -8).value(2).unwrap(), 3_000_000);
+        assert_eq!(price(150_000_000, -8).value(1).unwrap(), 1_500_000);
+    }
+
+    #[test]
+    fn value_is_unscaled_at_value_decimals() {
+        assert_eq!(price(1_500_000, -VALUE_DECIMALS).value(2).unwrap(), 3_000_000);
+    }
+
+    #[test]
+    fn value_shifts_up_for_large_exponents() {
+        assert_eq!(price(15_000, -4).value(2).unwrap(), 3_000_000);
+        assert_eq!(price(3, 0).value(2).unwrap(), 6_000_000);
+    }
+
+    #[test]
+    fn parse_pyth_reads_the_aggregate_price() {
+        let data = pyth_account(150_000_000, -8, PYTH_STATUS_TRADING);
+        assert_eq!(parse_pyth(&data).unwrap(), (150_000_000, -8, 25, 1_700));
+    }
+
+    #[test]
+    fn parse_pyth_rejects_bad_accounts() {
+        let halted = pyth_account(150_000_000, -8, 0);
+        assert!(parse_pyth(&halted).is_err());
+
+        let mut wrong_magic = pyth_account(150_000_000, -8, PYTH_STATUS_TRADING);
+        wrong_magic[0] ^= 1;
+        assert!(parse_pyth(&wrong_magic).is_err());
+
+        let short = pyth_account(150_000_000, -8, PYTH_STATUS_TRADING);
+        assert!(parse_pyth(&short[..PYTH_MIN_LEN - 1]).is_err());
+    }
+}
@@ -1,16 +1,59 @@
 use anchor_lang::prelude::*;
 
+#[account]
+pub struct ProgramConfig {
+    pub admin: Pubkey,
+    pub bump: u8,
+}
+
 #[account]
 pub struct Pool {
     pub authority: Pubkey,
     pub bump: u8,
+    pub mint: Pubkey,
+    pub vault: Pubkey,
     pub total_deposits: u64,
     pub total_borrowed: u64,
+    pub kyc_registry_root: [u8; 32],
+    pub base_rate_bps: u64,
+    pub slope1_bps: u64,
+    pub optimal_utilization_bps: u64,
+    pub slope2_bps: u64,
+    pub borrow_index: u128,
+    pub last_accrual_timestamp: i64,
+    pub oracle: Pubkey,
+    pub max_ltv_bps: u64,
+    pub liquidation_threshold_bps: u64,
+    pub close_factor_bps: u64,
+    pub liquidation_bonus_bps: u64,
+    pub share_mint: Pubkey,
+    pub total_shares: u64,
+    pub total_collateral: u64,
+    pub reserve_factor_bps: u64,
+    pub origination_fee_bps: u64,
+    pub protocol_reserves: u64,
+    pub treasury: Pubkey,
+    pub flash_loan_fee_bps: u64,
+    pub flash_loan_amount: u64,
+    pub deposit_cap: u64,
+    pub borrow_cap: u64,
+}
+
+#[account]
+pub struct PriceFeed {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub price: i64,
+    pub expo: i32,
+    pub conf: u64,
+    pub publish_time: i64,
+    pub bump: u8,
 }
 
 #[account]
 pub struct UserLoan {
     pub owner: Pubkey,
+    pub pool: Pubkey,
     pub collateral_amount: u64,
     pub collateral_encrypted: Vec<u8>,
     pub borrowed: u64,
@@ -18,6 +61,17 @@ pub struct UserLoan {
     pub liquidation_proof: Vec<u8>,
     pub liquidated: bool,
     pub deposit_timestamp: i64,
+    pub borrow_index_snapshot: u128,
+    pub active_auction: Pubkey,
+}
+
+#[account]
+pub struct BorrowAllowance {
+    pub owner: Pubkey,
+    pub delegate: Pubkey,
+    pub pool: Pubkey,
+    pub remaining: u64,
+    pub bump: u8,
 }
 
 #[account]
@@ -28,6 +82,46 @@ pub struct DarkPoolOrderBook {
     pub bump: u8,
 }
 
+#[account]
+pub struct MarketConfig {
+    pub authority: Pubkey,
+    pub market_id: u64,
+    pub min_order_size: u64,
+    pub max_order_size: u64,
+    pub min_price: u64,
+    pub max_price: u64,
+    pub kyc_registry_root: [u8; 32],
+    pub bump: u8,
+    pub base_mint: Pubkey,
+    pub quote_mint: Pubkey,
+    pub base_vault: Pubkey,
+    pub quote_vault: Pubkey,
+    pub base_lot_size: u64,
+    pub quote_lot_size: u64,
+    pub open_batch_id: u64,
+    pub sealed_batch_id: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum BatchStatus {
+    Open,
+    Sealed,
+    Settled,
+}
+
+#[account]
+pub struct Batch {
+    pub batch_id: u64,
+    pub market: Pubkey,
+    pub open_slot: u64,
+    pub close_slot: u64,
+    pub status: BatchStatus,
+    pub clearing_price: u64,
+    pub orders: Vec<Pubkey>,
+    pub fills: Vec<u64>,
+    pub bump: u8,
+}
+
 #[account]
 pub struct ProofAccount {
     pub owner: Pubkey,
@@ -39,17 +133,22 @@ pub struct ProofAccount {
 #[account]
 pub struct EncryptedOrder {
     pub owner: Pubkey,
+    pub market: Pubkey,
     pub order_id: u64,
     pub encrypted_data: Vec<u8>,
     pub proof_account: Pubkey,
     pub timestamp: i64,
     pub settled: bool,
     pub batch_id: u64,
+    pub is_bid: bool,
+    pub escrow_amount: u64,
+    pub expires_at: i64,
 }
 
 #[account]
 pub struct LiquidationAuction {
     pub position_owner: Pubkey,
+    pub pool: Pubkey,
     pub auction_id: u64,
     pub collateral_amount: u64,
     pub minimum_bid: u64,
@@ -68,4 +167,14 @@ pub struct EncryptedBid {
     pub encrypted_bid: Vec<u8>,
     pub bid_proof: Vec<u8>,
     pub timestamp: i64,
-}
\ No newline at end of file
+}
+
+#[account]
+pub struct VerifierConfig {
+    pub authority: Pubkey,
+    pub circuit_type: u64,
+    pub version: u64,
+    pub verifying_key: Vec<u8>,
+    pub pending_key: Vec<u8>,
+    pub bump: u8,
+}
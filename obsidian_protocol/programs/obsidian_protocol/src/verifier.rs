@@ -0,0 +1,229 @@
+use anchor_lang::prelude::*;
+use solana_bn254::prelude::{alt_bn128_addition, alt_bn128_multiplication, alt_bn128_pairing};
+use crate::constants::*;
+use crate::errors::ErrorCode;
+use crate::state::VerifierConfig;
+
+// Groth16 verifier for proofs produced by circuits/obsidian_circuits.
+//
+// Proof data layout: public inputs (big-endian field elements, in the order
+// `main` declares them) followed by the proof points A (G1), B (G2), C (G1).
+// Verifying key layout: alpha (G1), beta (G2), gamma (G2), delta (G2), IC (G1 per input + 1).
+
+// BN254 base field modulus
+const FIELD_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x97, 0x81, 0x6a, 0x91, 0x68, 0x71, 0xca, 0x8d, 0x3c, 0x20, 0x8c, 0x16, 0xd8, 0x7c, 0xfd, 0x47,
+];
+
+// BN254 scalar field modulus
+const SCALAR_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x28, 0x33, 0xe8, 0x48, 0x79, 0xb9, 0x70, 0x91, 0x43, 0xe1, 0xf5, 0x93, 0xf0, 0x00, 0x00, 0x01,
+];
+
+pub struct PublicInputs {
+    pub circuit_type: u64,
+    pub kyc_registry_root: [u8; 32],
+    pub user_balance: u64,
+    pub min_order_size: u64,
+    pub max_order_size: u64,
+    pub min_price: u64,
+    pub max_price: u64,
+    pub minimum_bid: u64,
+    pub collateral_value: u64,
+    pub loan_collateral: u64,
+    pub loan_debt: u64,
+    pub ltv_bps: u64,
+    pub user: Pubkey,
+}
+
+impl PublicInputs {
+    fn parse(fields: &[[u8; 32]]) -> Result<Self> {
+        Ok(Self {
+            circuit_type: field_to_u64(&fields[0])?,
+            kyc_registry_root: fields[1],
+            user_balance: field_to_u64(&fields[2])?,
+            min_order_size: field_to_u64(&fields[3])?,
+            max_order_size: field_to_u64(&fields[4])?,
+            min_price: field_to_u64(&fields[5])?,
+            max_price: field_to_u64(&fields[6])?,
+            minimum_bid: field_to_u64(&fields[7])?,
+            collateral_value: field_to_u64(&fields[8])?,
+            loan_collateral: field_to_u64(&fields[9])?,
+            loan_debt: field_to_u64(&fields[10])?,
+            ltv_bps: field_to_u64(&fields[11])?,
+            user: fields_to_pubkey(&fields[12], &fields[13])?,
+        })
+    }
+}
+
+// Every proof is bound to the wallet submitting it, so a proof copied from
+// another account's state can't be replayed by a different signer.
+pub fn verify_proof(config: &VerifierConfig, proof_data: &[u8], signer: &Pubkey) -> Result<PublicInputs> {
+    let verifying_key = config.verifying_key.as_slice();
+    require!(verifying_key.len() == VERIFYING_KEY_SIZE, ErrorCode::InvalidVerifyingKey);
+    require!(proof_data.len() == PROOF_DATA_SIZE, ErrorCode::InvalidProof);
+
+    let (inputs_bytes, proof) = proof_data.split_at(PUBLIC_INPUTS_SIZE);
+    let fields: Vec<[u8; 32]> = inputs_bytes
+        .chunks_exact(FIELD_SIZE)
+        .map(|chunk| chunk.try_into().unwrap())
+        .collect();
+    for field in &fields {
+        require!(*field < SCALAR_MODULUS, ErrorCode::InvalidProof);
+    }
+
+    let (alpha, rest) = verifying_key.split_at(G1_SIZE);
+    let (beta, rest) = rest.split_at(G2_SIZE);
+    let (gamma, rest) = rest.split_at(G2_SIZE);
+    let (delta, ic) = rest.split_at(G2_SIZE);
+
+    let (proof_a, rest) = proof.split_at(G1_SIZE);
+    let (proof_b, proof_c) = rest.split_at(G2_SIZE);
+
+    // vk_x = IC[0] + sum(input_i * IC[i + 1])
+    let mut vk_x = ic[..G1_SIZE].to_vec();
+    for (field, point) in fields.iter().zip(ic[G1_SIZE..].chunks_exact(G1_SIZE)) {
+        let product = alt_bn128_multiplication(&[point, field.as_slice()].concat())
+            .map_err(|_| error!(ErrorCode::ProofVerificationFailed))?;
+        vk_x = alt_bn128_addition(&[vk_x.as_slice(), product.as_slice()].concat())
+            .map_err(|_| error!(ErrorCode::ProofVerificationFailed))?;
+    }
+
+    // e(-A, B) * e(alpha, beta) * e(vk_x, gamma) * e(C, delta) == 1
+    let pairing_input = [
+        negate_g1(proof_a)?.as_slice(),
+        proof_b,
+        alpha,
+        beta,
+        vk_x.as_slice(),
+        gamma,
+        proof_c,
+        delta,
+    ]
+    .concat();
+    let result = alt_bn128_pairing(&pairing_input)
+        .map_err(|_| error!(ErrorCode::ProofVerificationFailed))?;
+    require!(
+        result.last() == Some(&1) && result[..result.len() - 1].iter().all(|b| *b == 0),
+        ErrorCode::ProofVerificationFailed
+    );
+
+    let inputs = PublicInputs::parse(&fields)?;
+    require!(inputs.circuit_type == config.circuit_type, ErrorCode::CircuitMismatch);
+    require!(inputs.user == *signer, ErrorCode::PublicInputMismatch);
+    Ok(inputs)
+}
+
+fn negate_g1(point: &[u8]) -> Result<Vec<u8>> {
+    let mut negated = point.to_vec();
+    let y = &point[FIELD_SIZE..];
+    require!(y < &FIELD_MODULUS[..], ErrorCode::InvalidProof);
+    if y.iter().all(|b| *b == 0) {
+        return Ok(negated);
+    }
+
+    let mut borrow = 0u16;
+    for i in (0..FIELD_SIZE).rev() {
+        let diff = 0x100 + FIELD_MODULUS[i] as u16 - y[i] as u16 - borrow;
+        negated[FIELD_SIZE + i] = diff as u8;
+        borrow = if diff < 0x100 { 1 } else { 0 };
+    }
+    Ok(negated)
+}
+
+// Pubkeys don't fit in one field, so the circuit takes them as two 128-bit halves
+fn fields_to_pubkey(high: &[u8; 32], low: &[u8; 32]) -> Result<Pubkey> {
+    let half = FIELD_SIZE / 2;
+    require!(
+        high[..half].iter().all(|b| *b == 0) && low[..half].iter().all(|b| *b == 0),
+        ErrorCode::PublicInputMismatch
+    );
+    let mut key = [0u8; 32];
+    key[..half].copy_from_slice(&high[half..]);
+    key[half..].copy_from_slice(&low[half..]);
+    Ok(Pubkey::new_from_array(key))
+}
+
+fn field_to_u64(field: &[u8; 32]) -> Result<u64> {
+    let (high, low) = field.split_at(FIELD_SIZE - U64_SIZE);
+    require!(high.iter().all(|b| *b == 0), ErrorCode::PublicInputMismatch);
+    Ok(u64::from_be_bytes(low.try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn g1_with_y(y: [u8; 32]) -> Vec<u8> {
+        let mut point = vec![1u8; FIELD_SIZE];
+        point.extend_from_slice(&y);
+        point
+    }
+
+    fn field(value: u64) -> [u8; 32] {
+        let mut field = [0u8; 32];
+        field[FIELD_SIZE - U64_SIZE..].copy_from_slice(&value.to_be_bytes());
+        field
+    }
+
+    #[test]
+    fn negate_g1_subtracts_y_from_the_modulus() {
+        let point = g1_with_y(field(2));
+        let negated = negate_g1(&point).unwrap();
+
+        let mut expected = FIELD_MODULUS;
+        expected[FIELD_SIZE - 1] -= 2;
+        assert_eq!(&negated[..FIELD_SIZE], &point[..FIELD_SIZE]);
+        assert_eq!(&negated[FIELD_SIZE..], &expected[..]);
+        assert_eq!(negate_g1(&negated).unwrap(), point);
+    }
+
+    #[test]
+    fn negate_g1_borrows_across_bytes() {
+        let point = g1_with_y(field(0x100));
+        let negated = negate_g1(&point).unwrap();
+
+        let mut expected = FIELD_MODULUS;
+        expected[FIELD_SIZE - 2] -= 1;
+        assert_eq!(&negated[FIELD_SIZE..], &expected[..]);
+    }
+
+    #[test]
+    fn negate_g1_keeps_the_point_at_infinity() {
+        let point = g1_with_y([0u8; 32]);
+        assert_eq!(negate_g1(&point).unwrap(), point);
+    }
+
+    #[test]
+    fn negate_g1_rejects_unreduced_coordinates() {
+        assert!(negate_g1(&g1_with_y(FIELD_MODULUS)).is_err());
+    }
+
+    #[test]
+    fn field_to_u64_reads_the_low_bytes() {
+        assert_eq!(field_to_u64(&field(0)).unwrap(), 0);
+        assert_eq!(field_to_u64(&field(u64::MAX)).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn field_to_u64_rejects_values_above_u64() {
+        let mut wide = field(1);
+        wide[FIELD_SIZE - U64_SIZE - 1] = 1;
+        assert!(field_to_u64(&wide).is_err());
+    }
+
+    #[test]
+    fn fields_to_pubkey_joins_the_key_halves() {
+        let key = Pubkey::new_unique();
+        let mut high = [0u8; 32];
+        let mut low = [0u8; 32];
+        high[16..].copy_from_slice(&key.to_bytes()[..16]);
+        low[16..].copy_from_slice(&key.to_bytes()[16..]);
+        assert_eq!(fields_to_pubkey(&high, &low).unwrap(), key);
+
+        high[0] = 1;
+        assert!(fields_to_pubkey(&high, &low).is_err());
+    }
+}
@@ -0,0 +1,129 @@
+import { execFileSync } from "child_process";
+import { copyFileSync, existsSync, mkdirSync, mkdtempSync, readdirSync, readFileSync, writeFileSync } from "fs";
+import { tmpdir } from "os";
+import { join, resolve } from "path";
+import { PublicKey } from "@solana/web3.js";
+import { Noir } from "@noir-lang/noir_js";
+import circuit from "../circuits/obsidian_circuits/target/obsidian_circuits.json";
+
+// Must match NUM_PUBLIC_INPUTS in programs/obsidian_protocol/src/constants.rs
+export const NUM_PUBLIC_INPUTS = 14;
+
+const CIRCUIT_NAME = "obsidian_circuits";
+const CIRCUIT_PATH = resolve(__dirname, `../circuits/obsidian_circuits/target/${CIRCUIT_NAME}.json`);
+const GROTH16_DIR = resolve(__dirname, "../circuits/obsidian_circuits/target/groth16");
+
+const FIELD_SIZE = 32;
+const G1_SIZE = 64;
+const G2_SIZE = 128;
+const GROTH16_PROOF_SIZE = G1_SIZE + G2_SIZE + G1_SIZE;
+// gnark witness header: public count, secret count, vector length
+const WITNESS_HEADER_SIZE = 12;
+
+function sunspot(args: string[]) {
+  execFileSync(process.env.SUNSPOT ?? "sunspot", args, { stdio: "inherit" });
+}
+
+function findOutput(dir: string, extension: string): string {
+  const file = readdirSync(dir).find((name) => name.endsWith(extension));
+  if (!file) {
+    throw new Error(`sunspot wrote no ${extension} file to ${dir}`);
+  }
+  return join(dir, file);
+}
+
+// gnark raw points are big-endian with G2 coordinates ordered (c1, c0), which
+// is already the alt_bn128 layout. Compressed points carry flags in the top
+// two bits of the first byte.
+function requireUncompressed(point: Buffer) {
+  if ((point[0] & 0xc0) !== 0) {
+    throw new Error("Expected uncompressed gnark points, see \"Proving\" in README.md");
+  }
+}
+
+function readPoints(data: Buffer, offset: number, sizes: number[]): [Buffer[], number] {
+  const points = sizes.map((size) => {
+    const point = data.subarray(offset, offset + size);
+    requireUncompressed(point);
+    offset += size;
+    return point;
+  });
+  return [points, offset];
+}
+
+// gnark's raw verifying key is alpha, beta and delta in G1, beta, gamma and
+// delta in G2, then the length-prefixed IC points. The program only needs
+// alpha (G1), beta, gamma, delta (G2) and IC.
+export function packVerifyingKey(vk: Buffer): Buffer {
+  const [[alpha, , beta, gamma, , delta], icOffset] =
+    readPoints(vk, 0, [G1_SIZE, G1_SIZE, G2_SIZE, G2_SIZE, G1_SIZE, G2_SIZE]);
+  const icCount = vk.readUInt32BE(icOffset);
+  if (icCount !== NUM_PUBLIC_INPUTS + 1) {
+    throw new Error(`Expected ${NUM_PUBLIC_INPUTS + 1} IC points, got ${icCount}`);
+  }
+  const [ic] = readPoints(vk, icOffset + 4, new Array(icCount).fill(G1_SIZE));
+  return Buffer.concat([alpha, beta, gamma, delta, ...ic]);
+}
+
+// Public inputs from the gnark public witness followed by A, B and C from the
+// raw proof. The on-chain verifier has no commitment check, so proofs must
+// not carry any.
+export function packProof(proof: Buffer, publicWitness: Buffer): Buffer {
+  const publicInputs = publicWitness.subarray(WITNESS_HEADER_SIZE);
+  if (publicInputs.length !== NUM_PUBLIC_INPUTS * FIELD_SIZE) {
+    throw new Error(`Expected ${NUM_PUBLIC_INPUTS} public inputs, got ${publicInputs.length / FIELD_SIZE}`);
+  }
+  const [points, commitmentsOffset] = readPoints(proof, 0, [G1_SIZE, G2_SIZE, G1_SIZE]);
+  if (proof.length > GROTH16_PROOF_SIZE && proof.readUInt32BE(commitmentsOffset) !== 0) {
+    throw new Error("Proofs with commitments are not supported by the on-chain verifier");
+  }
+  return Buffer.concat([publicInputs, ...points]);
+}
+
+// Circuit inputs for the submitting wallet, as the (user_key_hi, user_key_lo) halves
+export function userKeyInputs(user: PublicKey) {
+  const bytes = user.toBuffer();
+  return {
+    user_key_hi: "0x" + bytes.subarray(0, 16).toString("hex"),
+    user_key_lo: "0x" + bytes.subarray(16).toString("hex"),
+  };
+}
+
+// Runs the one-off trusted setup if it hasn't been done yet and returns the
+// verifying key in the layout VerifierConfig stores.
+export function loadVerifyingKey(): Buffer {
+  const circuitCopy = join(GROTH16_DIR, `${CIRCUIT_NAME}.json`);
+  if (!existsSync(circuitCopy)) {
+    mkdirSync(GROTH16_DIR, { recursive: true });
+    copyFileSync(CIRCUIT_PATH, circuitCopy);
+    sunspot(["compile", circuitCopy]);
+    sunspot(["setup", findOutput(GROTH16_DIR, ".ccs")]);
+  }
+  return packVerifyingKey(readFileSync(findOutput(GROTH16_DIR, ".vk")));
+}
+
+// Solves the witness with noir_js and proves it locally with sunspot, so no
+// private input leaves the machine. Returns public inputs followed by the
+// proof, as the program expects.
+export async function generateGroth16Proof(input: any): Promise<Buffer> {
+  const noir = new Noir(circuit as any);
+  const { witness } = await noir.execute(input);
+
+  // Everything sunspot reads sits in one scratch directory, so its outputs
+  // land there too
+  const workDir = mkdtempSync(join(tmpdir(), "obsidian-proof-"));
+  const circuitCopy = join(workDir, `${CIRCUIT_NAME}.json`);
+  const ccsCopy = join(workDir, `${CIRCUIT_NAME}.ccs`);
+  const pkCopy = join(workDir, `${CIRCUIT_NAME}.pk`);
+  copyFileSync(CIRCUIT_PATH, circuitCopy);
+  copyFileSync(findOutput(GROTH16_DIR, ".ccs"), ccsCopy);
+  copyFileSync(findOutput(GROTH16_DIR, ".pk"), pkCopy);
+  const witnessPath = join(workDir, `${CIRCUIT_NAME}.gz`);
+  writeFileSync(witnessPath, witness);
+  sunspot(["prove", circuitCopy, witnessPath, ccsCopy, pkCopy]);
+
+  return packProof(
+    readFileSync(findOutput(workDir, ".proof")),
+    readFileSync(findOutput(workDir, ".pw")),
+  );
+}
@@ -2,19 +2,30 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program, BN } from "@coral-xyz/anchor";
 import { ObsidianProtocol } from "../target/types/obsidian_protocol";
 import { PublicKey, SystemProgram, Keypair, Transaction } from "@solana/web3.js";
-import { BarretenbergBackend } from '@noir-lang/backend_barretenberg';
-import { Noir } from '@noir-lang/noir_js';
-import circuit from "../circuits/obsidian_circuits/target/obsidian_circuits.json";
+import { createMint, getOrCreateAssociatedTokenAccount, mintTo } from "@solana/spl-token";
+import { BarretenbergSync, Fr } from "@aztec/bb.js";
+import { generateGroth16Proof, loadVerifyingKey, userKeyInputs } from "./groth16";
 
 const FUNDING_AMOUNT = 100_000_000;
 const PROOF_CHUNK_SIZE = 800;
+const CIRCUIT_TYPE_COMPLIANCE = 1;
 const CIRCUIT_TYPE_DARK_POOL = 2;
+const MARKET_ID = new BN(Date.now());
+const ORDER_TTL_SECONDS = 3600;
+const BASE_LOT_SIZE = 100;
+const QUOTE_LOT_SIZE = 1_000;
+const USER_ID_HASH = 42n;
+const BPF_LOADER_UPGRADEABLE = new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111");
 
 interface TestConfig {
   program: Program<ObsidianProtocol>;
   provider: anchor.AnchorProvider;
   trader: Keypair;
   orderBookPda: PublicKey;
+  marketPda: PublicKey;
+  baseMint: PublicKey;
+  quoteMint: PublicKey;
+  kycRegistryRoot: number[];
 }
 
 class TestHelpers {
@@ -33,22 +44,54 @@ class TestHelpers {
     await provider.sendAndConfirm(tx);
   }
 
-  static async generateZKProof(input: any): Promise<Uint8Array> {
-    const backend = new BarretenbergBackend(circuit as any);
-    const noir = new Noir(circuit as any);
-    
-    const { witness } = await noir.execute(input);
-    const proofData = await backend.generateProof(witness);
-    
-    return proofData.proof;
+  // Mirrors prove_compliance: the leaf commits to the KYC identity and the
+  // wallet, and is hashed up an all-zero sibling path.
+  static async kycRegistryRoot(user: PublicKey): Promise<string> {
+    const bb = await BarretenbergSync.new();
+    const { user_key_hi, user_key_lo } = userKeyInputs(user);
+    let current = bb.pedersenHash(
+      [new Fr(USER_ID_HASH), new Fr(BigInt(user_key_hi)), new Fr(BigInt(user_key_lo))],
+      0
+    );
+    for (let i = 0; i < 8; i++) {
+      current = bb.pedersenHash([current, Fr.ZERO], 0);
+    }
+    return current.toString();
+  }
+
+  static emptyInput(user: PublicKey) {
+    return {
+      circuit_type: 0,
+      order_amount: 0,
+      order_price: 0,
+      user_balance: 0,
+      min_order_size: 0,
+      max_order_size: 0,
+      min_price: 0,
+      max_price: 0,
+      user_id_hash: 0,
+      kyc_registry_root: 0,
+      merkle_path: Array(8).fill(0),
+      merkle_indices: Array(8).fill(0),
+      bid_amount: 0,
+      bidder_balance: 0,
+      minimum_bid: 0,
+      collateral_value: 0,
+      loan_collateral: 0,
+      loan_debt: 0,
+      ltv_bps: 0,
+      ...userKeyInputs(user),
+    };
   }
 
   static createDarkPoolInput(
+    user: PublicKey,
     orderAmount: number,
     orderPrice: number,
     userBalance: number
   ) {
     return {
+      ...TestHelpers.emptyInput(user),
       circuit_type: CIRCUIT_TYPE_DARK_POOL,
       order_amount: orderAmount,
       order_price: orderPrice,
@@ -57,14 +100,15 @@ class TestHelpers {
       max_order_size: 1000,
       min_price: 10,
       max_price: 200,
-      user_id_hash: 0,
-      kyc_registry_root: 0,
-      merkle_path: Array(8).fill(0),
-      merkle_indices: Array(8).fill(0),
-      bid_amount: 0,
-      bidder_balance: 0,
-      minimum_bid: 0,
-      collateral_value: 0
+    };
+  }
+
+  static createComplianceInput(user: PublicKey, kycRegistryRoot: string) {
+    return {
+      ...TestHelpers.emptyInput(user),
+      circuit_type: CIRCUIT_TYPE_COMPLIANCE,
+      user_id_hash: USER_ID_HASH.toString(),
+      kyc_registry_root: kycRegistryRoot,
     };
   }
 
@@ -74,46 +118,90 @@ class TestHelpers {
     orderId: BN
   ) {
     const orderIdBytes = orderId.toArrayLike(Buffer, 'le', 8);
-    
+
     const [proofPda] = PublicKey.findProgramAddressSync(
       [Buffer.from("proof"), trader.toBuffer(), orderIdBytes],
       program.programId
     );
-    
+
     const [orderPda] = PublicKey.findProgramAddressSync(
       [Buffer.from("order"), trader.toBuffer(), orderIdBytes],
       program.programId
     );
-    
+
     return { proofPda, orderPda };
   }
 
+  static deriveBatchPda(program: Program<ObsidianProtocol>, batchId: BN) {
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("batch"), batchId.toArrayLike(Buffer, 'le', 8)],
+      program.programId
+    );
+  }
+
   static async storeProofInChunks(
     program: Program<ObsidianProtocol>,
     proofPda: PublicKey,
     proof: Uint8Array,
-    owner: Keypair
+    owner: Keypair,
+    isOrderProof: boolean
   ): Promise<number> {
     let chunkCount = 0;
-    
+
     for (let i = 0; i < proof.length; i += PROOF_CHUNK_SIZE) {
       const chunk = proof.slice(i, Math.min(i + PROOF_CHUNK_SIZE, proof.length));
-      
+
       await program.methods
-        .storeOrderProof(Buffer.from(chunk), true)
+        .storeOrderProof(Buffer.from(chunk), isOrderProof)
         .accountsPartial({
           proofAccount: proofPda,
           owner: owner.publicKey,
         })
         .signers([owner])
         .rpc();
-      
+
       chunkCount++;
     }
-    
+
     return chunkCount;
   }
 
+  static async registerVerifyingKey(
+    program: Program<ObsidianProtocol>,
+    authority: PublicKey,
+    circuitType: number,
+    verifyingKey: Buffer
+  ): Promise<void> {
+    const circuitTypeBytes = new BN(circuitType).toArrayLike(Buffer, 'le', 8);
+    const [verifierPda, bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("verifier"), circuitTypeBytes],
+      program.programId
+    );
+
+    try {
+      await program.methods
+        .initializeVerifier(new BN(circuitType), bump)
+        .accountsPartial({
+          verifierConfig: verifierPda,
+          authority,
+        })
+        .rpc();
+    } catch (error) {
+      console.log(`Verifier ${circuitType} already exists, rotating key...`);
+    }
+
+    for (let i = 0; i < verifyingKey.length; i += PROOF_CHUNK_SIZE) {
+      await program.methods
+        .storeVerifyingKey(verifyingKey.subarray(i, i + PROOF_CHUNK_SIZE))
+        .accountsPartial({ verifierConfig: verifierPda, authority })
+        .rpc();
+    }
+    await program.methods
+      .activateVerifyingKey()
+      .accountsPartial({ verifierConfig: verifierPda, authority })
+      .rpc();
+  }
+
   static async displayOrderData(
     program: Program<ObsidianProtocol>,
     orderPda: PublicKey,
@@ -128,14 +216,16 @@ class TestHelpers {
     console.log(`  Proof Account: ${orderAccount.proofAccount.toString()}`);
     console.log(`  Timestamp: ${new Date(orderAccount.timestamp.toNumber() * 1000).toISOString()}`);
     console.log(`  Settled: ${orderAccount.settled}`);
-    
+    console.log(`  Batch ID: ${orderAccount.batchId.toString()}`);
+    console.log(`  Escrow Amount: ${orderAccount.escrowAmount.toString()}`);
+
     const proofAccount = await program.account.proofAccount.fetch(proofPda);
     console.log("\nProof Account:");
     console.log(`  Owner: ${proofAccount.owner.toString()}`);
     console.log(`  Order ID: ${proofAccount.orderId.toString()}`);
     console.log(`  Order Proof: ${proofAccount.orderProof.length} bytes`);
     console.log(`  Compliance Proof: ${proofAccount.complianceProof.length} bytes`);
-    
+
     const orderBook = await program.account.darkPoolOrderBook.fetch(orderBookPda);
     console.log("\nOrder Book:");
     console.log(`  Authority: ${orderBook.authority.toString()}`);
@@ -150,18 +240,71 @@ describe("Obsidian Protocol Pool Tests", () => {
     provider: null as any,
     trader: Keypair.generate(),
     orderBookPda: null as any,
+    marketPda: null as any,
+    baseMint: null as any,
+    quoteMint: null as any,
+    kycRegistryRoot: null as any,
   };
 
-  before(() => {
+  before(async () => {
     anchor.setProvider(anchor.AnchorProvider.env());
     config.program = anchor.workspace.ObsidianProtocol as Program<ObsidianProtocol>;
     config.provider = anchor.AnchorProvider.env();
-    
+
     const [orderBookPda] = PublicKey.findProgramAddressSync(
       [Buffer.from("dark_pool")],
       config.program.programId
     );
     config.orderBookPda = orderBookPda;
+
+    const [marketPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("market"), MARKET_ID.toArrayLike(Buffer, 'le', 8)],
+      config.program.programId
+    );
+    config.marketPda = marketPda;
+
+    const root = await TestHelpers.kycRegistryRoot(config.trader.publicKey);
+    config.kycRegistryRoot = Array.from(Buffer.from(root.slice(2).padStart(64, "0"), "hex"));
+  });
+
+  it("Should initialize program config", async () => {
+    try {
+      const [programConfigPda, bump] = PublicKey.findProgramAddressSync(
+        [Buffer.from("program_config")],
+        config.program.programId
+      );
+      const [programDataPda] = PublicKey.findProgramAddressSync(
+        [config.program.programId.toBuffer()],
+        BPF_LOADER_UPGRADEABLE
+      );
+
+      await config.program.methods
+        .initializeProgramConfig(bump)
+        .accountsPartial({
+          programConfig: programConfigPda,
+          program: config.program.programId,
+          programData: programDataPda,
+          authority: config.provider.wallet.publicKey,
+        })
+        .rpc();
+
+      console.log("program config initialized");
+    } catch (error) {
+      console.log("Program config already exists, continuing...");
+    }
+  });
+
+  it("Should register Groth16 verifying keys", async () => {
+    const verifyingKey = loadVerifyingKey();
+    for (const circuitType of [CIRCUIT_TYPE_COMPLIANCE, CIRCUIT_TYPE_DARK_POOL]) {
+      await TestHelpers.registerVerifyingKey(
+        config.program,
+        config.provider.wallet.publicKey,
+        circuitType,
+        verifyingKey
+      );
+    }
+    console.log(`Registered ${verifyingKey.length} byte verifying key`);
   });
 
   it("Should initialize dark pool order book", async () => {
@@ -170,20 +313,69 @@ describe("Obsidian Protocol Pool Tests", () => {
         [Buffer.from("dark_pool")],
         config.program.programId
       );
-      
+
       await config.program.methods
         .initializeDarkPool(bump)
-        .accountsPartial({ 
-          authority: config.provider.wallet.publicKey 
+        .accountsPartial({
+          authority: config.provider.wallet.publicKey
         })
         .rpc();
-        
+
       console.log("pool order book initialized");
     } catch (error) {
       console.log("Order book already exists, continuing...");
     }
   });
 
+  it("Should initialize market config", async () => {
+    const [, bump] = PublicKey.findProgramAddressSync(
+      [Buffer.from("market"), MARKET_ID.toArrayLike(Buffer, 'le', 8)],
+      config.program.programId
+    );
+
+    await config.program.methods
+      .initializeMarket(
+        MARKET_ID,
+        new BN(10),
+        new BN(1000),
+        new BN(10),
+        new BN(200),
+        config.kycRegistryRoot,
+        bump
+      )
+      .accountsPartial({
+        orderBook: config.orderBookPda,
+        marketConfig: config.marketPda,
+        authority: config.provider.wallet.publicKey,
+      })
+      .rpc();
+
+    console.log("market config initialized");
+  });
+
+  it("Should create market escrow vaults", async () => {
+    const payer = (config.provider.wallet as anchor.Wallet).payer;
+    config.baseMint = await createMint(config.provider.connection, payer, payer.publicKey, null, 6);
+    config.quoteMint = await createMint(config.provider.connection, payer, payer.publicKey, null, 6);
+
+    const orderBook = await config.program.account.darkPoolOrderBook.fetch(config.orderBookPda);
+    const [batchPda, batchBump] = TestHelpers.deriveBatchPda(config.program, orderBook.nextBatchId);
+
+    await config.program.methods
+      .initializeMarketVaults(new BN(BASE_LOT_SIZE), new BN(QUOTE_LOT_SIZE), batchBump)
+      .accountsPartial({
+        marketConfig: config.marketPda,
+        baseMint: config.baseMint,
+        quoteMint: config.quoteMint,
+        orderBook: config.orderBookPda,
+        batch: batchPda,
+        authority: config.provider.wallet.publicKey,
+      })
+      .rpc();
+
+    console.log(`market vaults created, batch ${orderBook.nextBatchId.toString()} opened`);
+  });
+
   it("Should create and submit encrypted order with ZK proof", async () => {
     console.log("\n 1: Fund Trader Account ");
     await TestHelpers.fundAccount(
@@ -191,12 +383,26 @@ describe("Obsidian Protocol Pool Tests", () => {
       config.trader.publicKey,
       FUNDING_AMOUNT
     );
-    console.log(`Funded trader with ${FUNDING_AMOUNT / 1e9} SOL`);
+    const payer = (config.provider.wallet as anchor.Wallet).payer;
+    const traderBase = await getOrCreateAssociatedTokenAccount(
+      config.provider.connection,
+      payer,
+      config.baseMint,
+      config.trader.publicKey
+    );
+    await mintTo(config.provider.connection, payer, config.baseMint, traderBase.address, payer, BASE_LOT_SIZE);
+    console.log(`Funded trader with ${FUNDING_AMOUNT / 1e9} SOL and ${BASE_LOT_SIZE} base`);
 
-    console.log("\n 2: Generate ZK Proof ");
-    const input = TestHelpers.createDarkPoolInput(50, 150, 100);
-    const proof = await TestHelpers.generateZKProof(input);
-    console.log(`Generated proof: ${proof.length} bytes`);
+    console.log("\n 2: Generate ZK Proofs ");
+    // Ask at escrow tier 0 locks exactly one base lot, which the order proof must show
+    const orderProof = await generateGroth16Proof(
+      TestHelpers.createDarkPoolInput(config.trader.publicKey, 50, 150, BASE_LOT_SIZE)
+    );
+    const root = "0x" + Buffer.from(config.kycRegistryRoot).toString("hex");
+    const complianceProof = await generateGroth16Proof(
+      TestHelpers.createComplianceInput(config.trader.publicKey, root)
+    );
+    console.log(`Generated proofs: ${orderProof.length} + ${complianceProof.length} bytes`);
 
     console.log("\n 3: Derive PDAs ");
     const orderId = new BN(Date.now());
@@ -205,6 +411,8 @@ describe("Obsidian Protocol Pool Tests", () => {
       config.trader.publicKey,
       orderId
     );
+    const market = await config.program.account.marketConfig.fetch(config.marketPda);
+    const [batchPda] = TestHelpers.deriveBatchPda(config.program, market.openBatchId);
     console.log(`Order ID: ${orderId.toString()}`);
     console.log(`Proof PDA: ${proofPda.toString()}`);
     console.log(`Order PDA: ${orderPda.toString()}`);
@@ -221,34 +429,52 @@ describe("Obsidian Protocol Pool Tests", () => {
       .rpc();
     console.log("Proof account created");
 
-    console.log("\n 5: Store Proof in Chunks ");
-    const chunkCount = await TestHelpers.storeProofInChunks(
+    console.log("\n 5: Store Proofs in Chunks ");
+    const orderChunks = await TestHelpers.storeProofInChunks(
       config.program,
       proofPda,
-      proof,
-      config.trader
+      orderProof,
+      config.trader,
+      true
     );
-    console.log(`Stored proof in ${chunkCount} chunks`);
-
-    await config.program.methods
-      .storeOrderProof(Buffer.from([0x01, 0x02, 0x03]), false)
-      .accountsPartial({
-        proofAccount: proofPda,
-        owner: config.trader.publicKey,
-      })
-      .signers([config.trader])
-      .rpc();
-    console.log("Compliance proof stored");
+    const complianceChunks = await TestHelpers.storeProofInChunks(
+      config.program,
+      proofPda,
+      complianceProof,
+      config.trader,
+      false
+    );
+    console.log(`Stored proofs in ${orderChunks} + ${complianceChunks} chunks`);
 
     console.log("\n 6: Submit Encrypted Order ");
     const encryptedData = Buffer.from("ENCRYPTED_ORDER_DATA");
-    
+    const [orderVerifierPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("verifier"), new BN(CIRCUIT_TYPE_DARK_POOL).toArrayLike(Buffer, 'le', 8)],
+      config.program.programId
+    );
+    const [complianceVerifierPda] = PublicKey.findProgramAddressSync(
+      [Buffer.from("verifier"), new BN(CIRCUIT_TYPE_COMPLIANCE).toArrayLike(Buffer, 'le', 8)],
+      config.program.programId
+    );
+
     const tx = await config.program.methods
-      .submitEncryptedOrder(orderId, encryptedData)
+      .submitEncryptedOrder(
+        orderId,
+        encryptedData,
+        false,
+        0,
+        new BN(Math.floor(Date.now() / 1000) + ORDER_TTL_SECONDS)
+      )
       .accountsPartial({
         orderBook: config.orderBookPda,
+        marketConfig: config.marketPda,
+        batch: batchPda,
         order: orderPda,
         proofAccount: proofPda,
+        userTokenAccount: traderBase.address,
+        escrowVault: market.baseVault,
+        orderVerifier: orderVerifierPda,
+        complianceVerifier: complianceVerifierPda,
         user: config.trader.publicKey,
         systemProgram: SystemProgram.programId,
       })
@@ -267,4 +493,4 @@ describe("Obsidian Protocol Pool Tests", () => {
 
     console.log("\n success: Dark pool order with MEV protection ");
   });
-});
\ No newline at end of file
+});